@@ -0,0 +1,40 @@
+//! Writes 64 KB payloads into `/dev/null`.
+//!
+//! Run with `cargo bench`.
+
+use std::time::Instant;
+
+use fd_writer::FdWriter;
+
+const PAYLOAD_SIZE: usize = 64 * 1024;
+const ITERATIONS: u32 = 10_000;
+
+fn bench<F: FnMut(&mut FdWriter, &[u8])>(name: &str, fd: libc::c_int, mut write: F) {
+    let payload = vec![b'a'; PAYLOAD_SIZE];
+    let mut writer = FdWriter::new(fd);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        write(&mut writer, &payload);
+    }
+    writer.flush();
+    let elapsed = start.elapsed();
+
+    println!("{}: {:?} per 64 KB payload", name, elapsed / ITERATIONS);
+}
+
+fn main() {
+    let fd = unsafe {
+        libc::open("/dev/null\0".as_ptr() as *const _, libc::O_WRONLY)
+    };
+    assert!(fd >= 0, "Unable to open /dev/null");
+
+    bench("write_data(64 KB)", fd, |writer, payload| writer.write_data(payload));
+    bench("write_data(1 KB) x 64", fd, |writer, payload| for chunk in payload.chunks(1024) {
+        writer.write_data(chunk);
+    });
+
+    unsafe {
+        libc::close(fd);
+    }
+}
@@ -0,0 +1,170 @@
+use core::fmt;
+
+use crate::{Buffer, FdBackedSink, FdWriter};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum State {
+    Text,
+    //After `ESC`
+    Escape,
+    //After `ESC` and intermediate bytes, e.g. `ESC (`
+    Intermediate,
+    //After `ESC [`
+    Csi,
+    //After `ESC ]` (OSC) or other string sequence, until `BEL` or `ESC \`
+    Osc,
+}
+
+///Writer that removes ANSI escape sequences (e.g. colors) from data written into `W`.
+///
+///Sequence may be split across several writes, as state is kept between them.
+///
+///Following sequences are removed:
+///- CSI sequences (`ESC [`, parameters and final byte);
+///- OSC and other string sequences (`ESC ]`, `ESC P`, `ESC X`, `ESC ^`, `ESC _`), terminated by `BEL` or `ESC \`;
+///- Other escapes (`ESC`, optional intermediate bytes and final byte, e.g. `ESC 7` or `ESC ( B`).
+///
+///Malformed sequence is dropped up to the first byte that cannot be part of it, which is written as text.
+pub struct StripAnsiWriter<W> {
+    inner: W,
+    enabled: bool,
+    state: State,
+}
+
+impl<W> StripAnsiWriter<W> {
+    #[inline(always)]
+    ///Creates new instance which writes into `inner`, removing escape sequences
+    pub const fn new(inner: W) -> Self {
+        Self {
+            inner,
+            enabled: true,
+            state: State::Text,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns whether escape sequences are removed.
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    #[inline(always)]
+    ///Sets whether escape sequences are removed, which is enabled by default.
+    ///
+    ///When disabled, data is written as it is.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.state = State::Text;
+    }
+
+    #[inline(always)]
+    ///Returns reference to underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline(always)]
+    ///Returns mutable reference to underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline(always)]
+    ///Returns underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    //Skips escape sequences at the start of `data`, returning number of skipped bytes and length of text after them.
+    //
+    //Text ends before next `ESC`, which is left to be skipped by next call.
+    fn scan(&mut self, data: &[u8]) -> (usize, usize) {
+        if !self.enabled {
+            return (0, data.len());
+        }
+
+        let mut skip = 0;
+        while let Some(byte) = data.get(skip) {
+            match (self.state, *byte) {
+                (_, ESC) => self.state = State::Escape,
+                (State::Text, _) => break,
+                (State::Escape, b'[') => self.state = State::Csi,
+                (State::Escape, b']' | b'P' | b'X' | b'^' | b'_') => self.state = State::Osc,
+                (State::Escape | State::Intermediate, 0x20..=0x2f) => self.state = State::Intermediate,
+                (State::Escape | State::Intermediate, 0x30..=0x7e) | (State::Csi, 0x40..=0x7e) | (State::Osc, BEL) => self.state = State::Text,
+                (State::Csi, 0x20..=0x3f) | (State::Osc, _) => (),
+                (State::Escape | State::Intermediate | State::Csi, _) => {
+                    //Malformed sequence, byte is written as text
+                    self.state = State::Text;
+                    break;
+                },
+            }
+            skip += 1;
+        }
+
+        let text = &data[skip..];
+        (skip, text.iter().position(|byte| *byte == ESC).unwrap_or(text.len()))
+    }
+}
+
+impl<const N: usize, S: FdBackedSink, B: Buffer> StripAnsiWriter<FdWriter<N, S, B>> {
+    #[inline]
+    ///Creates new instance which writes into `inner`, removing escape sequences only if it is not terminal
+    pub fn new_auto(inner: FdWriter<N, S, B>) -> Self {
+        let mut this = Self::new(inner);
+        this.enabled = !this.inner.is_terminal();
+        this
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for StripAnsiWriter<W> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("StripAnsiWriter").field("inner", &self.inner).field("enabled", &self.enabled).finish()
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for StripAnsiWriter<W> {
+    fn write_str(&mut self, mut text: &str) -> fmt::Result {
+        while !text.is_empty() {
+            let (skip, len) = self.scan(text.as_bytes());
+            //Text always starts after ASCII byte or at the start, hence it is on character boundary
+            let part = &text[skip..skip + len];
+            if !part.is_empty() {
+                self.inner.write_str(part)?;
+            }
+            text = &text[skip + len..];
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> std::io::Write for StripAnsiWriter<W> {
+    ///Writes text up to next escape sequence, returning number of consumed bytes, including removed escape sequences.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let (skip, len) = self.scan(buf);
+        if len == 0 {
+            return Ok(skip);
+        }
+
+        match self.inner.write(&buf[skip..skip + len]) {
+            Ok(written) => Ok(skip + written),
+            //Skipped bytes are already consumed, hence they must be reported
+            Err(_) if skip > 0 => Ok(skip),
+            Err(error) => Err(error),
+        }
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,155 @@
+use core::{fmt, marker, ops};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+
+use crate::{FdBackedSink, FdSink, FdWriter, RawSink, BUFFER_CAPACITY};
+
+///Sink of `BorrowedFdWriter`.
+///
+///Unlike `FdSink`, it is bound to lifetime of borrowed file descriptor,
+///hence file descriptor cannot be replaced via `BorrowedFdWriter`.
+pub struct BorrowedFdSink<'fd> {
+    inner: FdSink,
+    _fd: marker::PhantomData<BorrowedFd<'fd>>,
+}
+
+impl<'fd> BorrowedFdSink<'fd> {
+    #[inline(always)]
+    fn new(fd: BorrowedFd<'fd>) -> Self {
+        Self {
+            inner: FdSink::new(fd.as_raw_fd()),
+            _fd: marker::PhantomData,
+        }
+    }
+}
+
+impl<'fd> FdBackedSink for BorrowedFdSink<'fd> {
+    #[inline(always)]
+    fn fd(&self) -> RawFd {
+        self.inner.fd
+    }
+
+    #[inline(always)]
+    fn set_ignore_sigpipe(&mut self, enabled: bool) {
+        self.inner.set_ignore_sigpipe(enabled);
+    }
+}
+
+impl<'fd> RawSink for BorrowedFdSink<'fd> {
+    #[inline(always)]
+    fn raw_write(&mut self, data: &[u8]) -> Result<usize, libc::c_int> {
+        self.inner.raw_write(data)
+    }
+
+    #[inline(always)]
+    fn raw_write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+        self.inner.raw_write_vectored(bufs)
+    }
+}
+
+impl<'fd> fmt::Debug for BorrowedFdSink<'fd> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("BorrowedFdSink").field("fd", &self.inner.fd).finish()
+    }
+}
+
+///Writer that borrows file descriptor, making sure it doesn't outlive it.
+///
+///All writing is done through underlying `FdWriter`, accessible via `Deref`.
+pub struct BorrowedFdWriter<'fd, const N: usize = BUFFER_CAPACITY> {
+    inner: FdWriter<N, BorrowedFdSink<'fd>>,
+}
+
+impl<'fd, const N: usize> BorrowedFdWriter<'fd, N> {
+    #[inline(always)]
+    ///Creates new instance with buffer capacity `N`, which writes into `fd`.
+    pub fn new_sized(fd: BorrowedFd<'fd>) -> Self {
+        Self {
+            inner: FdWriter::with_sink_sized(BorrowedFdSink::new(fd)),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns underlying file descriptor.
+    pub const fn fd(&self) -> RawFd {
+        self.inner.sink.inner.fd
+    }
+}
+
+impl<'fd, const N: usize> ops::Deref for BorrowedFdWriter<'fd, N> {
+    type Target = FdWriter<N, BorrowedFdSink<'fd>>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'fd, const N: usize> ops::DerefMut for BorrowedFdWriter<'fd, N> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<'fd, const N: usize> fmt::Debug for BorrowedFdWriter<'fd, N> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("BorrowedFdWriter").field("fd", &self.inner.sink.inner.fd).field("buffered", &self.inner.len).finish()
+    }
+}
+
+impl<'fd, const N: usize> fmt::Write for BorrowedFdWriter<'fd, N> {
+    #[inline(always)]
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        fmt::Write::write_str(&mut self.inner, text)
+    }
+}
+
+impl<'fd, const N: usize> std::io::Write for BorrowedFdWriter<'fd, N> {
+    #[inline(always)]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        std::io::Write::write(&mut self.inner, buf)
+    }
+
+    #[inline(always)]
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        std::io::Write::write_vectored(&mut self.inner, bufs)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::Write::flush(&mut self.inner)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'fd, const N: usize> std::io::Seek for BorrowedFdWriter<'fd, N> {
+    #[inline(always)]
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        std::io::Seek::seek(&mut self.inner, pos)
+    }
+}
+
+impl<'fd, const N: usize> AsRawFd for BorrowedFdWriter<'fd, N> {
+    #[inline(always)]
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.sink.inner.fd
+    }
+}
+
+impl<'fd, const N: usize> AsFd for BorrowedFdWriter<'fd, N> {
+    #[inline(always)]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        //File descriptor is borrowed for `'fd`, which outlives writer.
+        unsafe {
+            BorrowedFd::borrow_raw(self.inner.sink.inner.fd)
+        }
+    }
+}
+
+impl<'fd, const N: usize> From<BorrowedFd<'fd>> for BorrowedFdWriter<'fd, N> {
+    #[inline(always)]
+    fn from(fd: BorrowedFd<'fd>) -> Self {
+        Self::new_sized(fd)
+    }
+}
@@ -0,0 +1,67 @@
+use core::mem;
+
+///Storage of `FdWriter` buffer.
+///
+///# Safety
+///
+///Pointers must be valid for `capacity` bytes, and neither pointers nor capacity may change while storage is in use.
+pub unsafe trait Buffer {
+    ///Returns pointer to first byte of storage.
+    fn as_ptr(&self) -> *const u8;
+    ///Returns mutable pointer to first byte of storage.
+    fn as_mut_ptr(&mut self) -> *mut u8;
+    ///Returns capacity of storage.
+    fn capacity(&self) -> usize;
+}
+
+unsafe impl<const N: usize> Buffer for mem::MaybeUninit<[u8; N]> {
+    #[inline(always)]
+    fn as_ptr(&self) -> *const u8 {
+        mem::MaybeUninit::as_ptr(self) as *const u8
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        mem::MaybeUninit::as_mut_ptr(self) as *mut u8
+    }
+
+    #[inline(always)]
+    fn capacity(&self) -> usize {
+        N
+    }
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl Buffer for alloc::boxed::Box<[mem::MaybeUninit<u8>]> {
+    #[inline(always)]
+    fn as_ptr(&self) -> *const u8 {
+        <[mem::MaybeUninit<u8>]>::as_ptr(self) as *const u8
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        <[mem::MaybeUninit<u8>]>::as_mut_ptr(self) as *mut u8
+    }
+
+    #[inline(always)]
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+unsafe impl Buffer for &mut [u8] {
+    #[inline(always)]
+    fn as_ptr(&self) -> *const u8 {
+        <[u8]>::as_ptr(self)
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        <[u8]>::as_mut_ptr(self)
+    }
+
+    #[inline(always)]
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+}
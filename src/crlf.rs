@@ -0,0 +1,114 @@
+use core::fmt;
+
+///Writer that translates every `\n` into `\r\n`, when writing into `W`.
+///
+///`\n` that is already preceded by `\r` is written as it is, even if `\r` came in previous write.
+pub struct CrlfWriter<W> {
+    inner: W,
+    after_cr: bool,
+}
+
+impl<W> CrlfWriter<W> {
+    #[inline(always)]
+    ///Creates new instance which writes into `inner`, translating line endings
+    pub const fn new(inner: W) -> Self {
+        Self {
+            inner,
+            after_cr: false,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns reference to underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline(always)]
+    ///Returns mutable reference to underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline(always)]
+    ///Returns underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    //Returns line ending to be written instead of `\n`.
+    fn line_ending(&self) -> &'static str {
+        match self.after_cr {
+            true => "\n",
+            false => "\r\n",
+        }
+    }
+
+    //Updates state after `text`, that doesn't contain `\n`, is written.
+    fn end_text(&mut self, text: &[u8]) {
+        if let Some(byte) = text.last() {
+            self.after_cr = *byte == b'\r';
+        }
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for CrlfWriter<W> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("CrlfWriter").field("inner", &self.inner).finish()
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for CrlfWriter<W> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for line in text.split_inclusive('\n') {
+            let text = line.strip_suffix('\n').unwrap_or(line);
+            if !text.is_empty() {
+                self.inner.write_str(text)?;
+                self.end_text(text.as_bytes());
+            }
+
+            if text.len() < line.len() {
+                self.inner.write_str(self.line_ending())?;
+                self.after_cr = false;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> std::io::Write for CrlfWriter<W> {
+    ///Writes `buf` up to next `\n` or single translated `\n`, returning number of consumed bytes.
+    ///
+    ///If line ending is written partially, then error is returned and written part is not repeated on next write.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.first() == Some(&b'\n') {
+            loop {
+                let ending = self.line_ending().as_bytes();
+                match self.inner.write(ending)? {
+                    0 => return Err(std::io::ErrorKind::WriteZero.into()),
+                    written if written == ending.len() => break,
+                    //Only `\r` is written
+                    _ => self.after_cr = true,
+                }
+            }
+            self.after_cr = false;
+            return Ok(1);
+        }
+
+        let text = match buf.iter().position(|byte| *byte == b'\n') {
+            Some(idx) => &buf[..idx],
+            None => buf,
+        };
+        let written = self.inner.write(text)?;
+        self.end_text(&text[..written]);
+
+        Ok(written)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,68 @@
+//! `defmt` global logger writing frames into stderr.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{sys, BufferMode, FdWriter};
+
+struct State {
+    encoder: defmt::Encoder,
+    //Frames are binary, hence they are flushed on release instead of on `\n`.
+    writer: FdWriter,
+    restore: critical_section::RestoreState,
+}
+
+struct Shared(UnsafeCell<State>);
+
+//State is only accessed within critical section, while logger is taken.
+unsafe impl Sync for Shared {}
+
+static TAKEN: AtomicBool = AtomicBool::new(false);
+static STATE: Shared = Shared(UnsafeCell::new(State {
+    encoder: defmt::Encoder::new(),
+    writer: FdWriter::with_mode(sys::STDERR, BufferMode::Full),
+    restore: critical_section::RestoreState::invalid(),
+}));
+
+#[defmt::global_logger]
+struct Logger;
+
+unsafe impl defmt::Logger for Logger {
+    fn acquire() {
+        let restore = unsafe {
+            critical_section::acquire()
+        };
+
+        if TAKEN.swap(true, Ordering::Relaxed) {
+            panic!("defmt logger is taken reentrantly");
+        }
+
+        let state = unsafe {
+            &mut *STATE.0.get()
+        };
+        state.restore = restore;
+        let writer = &mut state.writer;
+        state.encoder.start_frame(|data| writer.write_data(data));
+    }
+
+    unsafe fn flush() {
+        let state = &mut *STATE.0.get();
+        state.writer.flush();
+    }
+
+    unsafe fn release() {
+        let state = &mut *STATE.0.get();
+        let writer = &mut state.writer;
+        state.encoder.end_frame(|data| writer.write_data(data));
+        writer.flush();
+
+        TAKEN.store(false, Ordering::Relaxed);
+        critical_section::release(state.restore);
+    }
+
+    unsafe fn write(bytes: &[u8]) {
+        let state = &mut *STATE.0.get();
+        let writer = &mut state.writer;
+        state.encoder.write(bytes, |data| writer.write_data(data));
+    }
+}
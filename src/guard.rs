@@ -0,0 +1,58 @@
+use core::{fmt, mem, ops};
+
+use crate::{Buffer, FdWriter, RawSink, WriteError};
+
+///Guard that flushes borrowed `FdWriter` at the end of scope, see `FdWriter::flush_guard`.
+///
+///`finish` flushes and returns error, while dropping guard without it flushes ignoring errors.
+pub struct FlushGuard<'a, const N: usize, S: RawSink, B: Buffer> {
+    writer: &'a mut FdWriter<N, S, B>,
+}
+
+impl<'a, const N: usize, S: RawSink, B: Buffer> FlushGuard<'a, N, S, B> {
+    #[inline(always)]
+    pub(crate) fn new(writer: &'a mut FdWriter<N, S, B>) -> Self {
+        Self {
+            writer,
+        }
+    }
+
+    #[inline]
+    ///Flushes writer, returning error if flush fails.
+    pub fn finish(self) -> Result<(), WriteError> {
+        let result = self.writer.try_flush();
+        //Guard only holds reference, hence there is nothing else to drop.
+        mem::forget(self);
+        result
+    }
+}
+
+impl<const N: usize, S: RawSink, B: Buffer> ops::Deref for FlushGuard<'_, N, S, B> {
+    type Target = FdWriter<N, S, B>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.writer
+    }
+}
+
+impl<const N: usize, S: RawSink, B: Buffer> ops::DerefMut for FlushGuard<'_, N, S, B> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.writer
+    }
+}
+
+impl<const N: usize, S: RawSink + fmt::Debug, B: Buffer> fmt::Debug for FlushGuard<'_, N, S, B> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.writer, fmt)
+    }
+}
+
+impl<const N: usize, S: RawSink, B: Buffer> Drop for FlushGuard<'_, N, S, B> {
+    #[inline]
+    fn drop(&mut self) {
+        self.writer.flush();
+    }
+}
@@ -1,128 +1,1677 @@
 //! Simple writer to file descriptor using libc.
 //!
+//! On Windows `HANDLE` is used instead of file descriptor, writing via `WriteFile`.
+//!
+//! On WASI data is written via `fd_write` import directly.
+//!
+//! ## Capacity:
+//!
+//! Buffer is stored inline and its capacity is set by const parameter, which is `BUFFER_CAPACITY` by default.
+//!
+//! ```rust
+//! use fd_writer::FdWriter;
+//!
+//! let default = FdWriter::new(1);
+//! let small = FdWriter::<64>::new_sized(1);
+//! ```
+//!
+//! With `alloc` feature `FdWriterBoxed` stores buffer on heap instead, choosing its capacity at runtime.
+//!
+//! ```rust
+//! # #[cfg(feature = "alloc")]
+//! let bulk = fd_writer::FdWriterBoxed::with_capacity(1, 256 * 1024);
+//! ```
+//!
+//! `FdWriterRef` uses caller provided slice as buffer, which is useful when storage is already available.
+//!
+//! ```rust
+//! let mut buffer = [0u8; 100];
+//! let writer = fd_writer::FdWriterRef::with_buffer(1, &mut buffer);
+//! assert_eq!(writer.capacity(), 100);
+//! ```
+//!
+//! ## Sink:
+//!
+//! Data is written into file descriptor by default, but any `RawSink` can be used instead via `FdWriter::with_sink`.
+//! With `std` feature it is implemented for `Vec<u8>`, which is useful to inspect output in tests.
+//!
+//! ## Macros:
+//!
+//! `fd_print!`, `fd_println!`, `fd_eprint!` and `fd_eprintln!` write into shared writers, returned by `stdout()` and `stderr()`.
+//! Errors are ignored and `ln` variants always flush.
+//!
+//! ## Errors:
+//!
+//! Flushing keeps calling `write` until whole buffer is written out.
+//! Writes interrupted by signal (`EINTR`) are always retried, without any limit on number of attempts.
+//! Any other error stops flushing, leaving unwritten data in buffer.
+//!
 //! ## Features:
 //!
-//! - `std` - Enables `std::io::Write` implementation.
+//! - `alloc` - Enables `FdWriterBoxed` with heap allocated buffer.
+//! - `std` - Enables `alloc` feature, `std::io::Write` implementation and `std::os::fd` traits on unix or `std::os::windows::io` handle traits on Windows.
+//! - `ufmt` - Implements `ufmt::uWrite` for `FdWriter`, failing with `WriteError`.
+//! - `async-tokio` - Enables `std` feature and implements `tokio::io::AsyncWrite` for `FdWriter`, which fails with `WouldBlock` on `EAGAIN`.
+//! - `tokio` - Enables `async-tokio` feature and `AsyncFdWriter`, which waits for file descriptor to become writable using tokio reactor, on unix.
+//! - `log` - Enables `FdWriterLogger`, implementing `log::Log`.
+//! - `defmt` - Installs `defmt` global logger, which writes frames into stderr. Requires `critical-section` implementation.
+//! - `rustix` - Performs syscalls via `rustix` instead of `libc` on unix.
 //!
 
-#![cfg_attr(not(test), no_std)]
-#![warn(missing_docs)]
+#![cfg_attr(not(test), no_std)]
+#![warn(missing_docs)]
+
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::{slice, cmp, mem, ptr, fmt};
+
+mod macros;
+mod sys;
+pub use sys::{RawFd, PIPE_BUF};
+mod sink;
+mod buffer;
+pub use buffer::Buffer;
+pub use sink::{RawSink, FdSink, FdBackedSink};
+mod owned;
+pub use owned::{OwnedFdWriter, OwnedFdSink};
+mod guard;
+pub use guard::FlushGuard;
+mod sync;
+pub use sync::{FdWriterSync, FdWriterGuard, stdout, stderr};
+mod tee;
+pub use tee::TeeWriter;
+mod prefixed;
+pub use prefixed::{PrefixedWriter, LinePrefix};
+mod timestamp;
+pub use timestamp::{TimestampWriter, Timestamp, Clock};
+mod ansi;
+pub use ansi::StripAnsiWriter;
+mod crlf;
+pub use crlf::CrlfWriter;
+mod limited;
+pub use limited::LimitedWriter;
+mod panic;
+pub use panic::write_panic_info;
+#[cfg(feature = "ufmt")]
+mod ufmt;
+#[cfg(feature = "async-tokio")]
+mod tokio;
+#[cfg(all(feature = "tokio", unix))]
+pub use self::tokio::AsyncFdWriter;
+#[cfg(feature = "log")]
+mod logger;
+#[cfg(feature = "log")]
+pub use logger::FdWriterLogger;
+#[cfg(feature = "defmt")]
+mod defmt_logger;
+#[cfg(all(feature = "std", unix))]
+mod borrowed;
+#[cfg(all(feature = "std", unix))]
+pub use borrowed::{BorrowedFdWriter, BorrowedFdSink};
+#[cfg(test)]
+mod tests;
+
+///Default capacity of `FdWriter` buffer.
+pub const BUFFER_CAPACITY: usize = 4096;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+///Error of writing into file descriptor.
+pub struct WriteError {
+    errno: libc::c_int,
+    written: usize,
+}
+
+impl WriteError {
+    #[inline(always)]
+    ///Creates new error from `errno` value.
+    pub const fn new(errno: libc::c_int) -> Self {
+        Self {
+            errno,
+            written: 0,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns raw `errno` value, which is result of `GetLastError` on Windows.
+    ///
+    ///`0` indicates that `write` stopped short without error: it was unable to write any byte,
+    ///or `write_atomic` was only able to write part of data.
+    pub const fn errno(&self) -> libc::c_int {
+        self.errno
+    }
+
+    #[cfg(not(windows))]
+    #[inline(always)]
+    ///Returns whether error indicates that write would block on non-blocking file descriptor.
+    pub const fn is_would_block(&self) -> bool {
+        self.errno == libc::EAGAIN || self.errno == libc::EWOULDBLOCK
+    }
+
+    #[cfg(windows)]
+    #[inline(always)]
+    ///Returns whether error indicates that write would block on non-blocking handle.
+    ///
+    ///This is `ERROR_NO_DATA` for non-blocking pipe or `WSAEWOULDBLOCK` for socket.
+    pub const fn is_would_block(&self) -> bool {
+        //`ERROR_NO_DATA`
+        const NO_DATA: libc::c_int = 232;
+        //`WSAEWOULDBLOCK`
+        const WSA_WOULD_BLOCK: libc::c_int = 10035;
+
+        self.errno == NO_DATA || self.errno == WSA_WOULD_BLOCK
+    }
+
+    #[inline(always)]
+    ///Returns number of bytes that were written out before error happened.
+    pub const fn written(&self) -> usize {
+        self.written
+    }
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errno {
+            0 => write!(fmt, "write stopped short after {} bytes", self.written),
+            errno => write!(fmt, "write failed with errno {} after {} bytes", errno, self.written),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WriteError {
+}
+
+#[cfg(feature = "std")]
+impl From<WriteError> for std::io::Error {
+    #[inline]
+    fn from(error: WriteError) -> Self {
+        match error.errno {
+            0 => std::io::ErrorKind::WriteZero.into(),
+            errno => std::io::Error::from_raw_os_error(errno),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+///Statistics of `FdWriter`.
+pub struct Stats {
+    ///Total number of bytes written.
+    pub bytes_written: u64,
+    ///Number of write calls.
+    pub writes: u64,
+    ///Number of write calls that wrote less than requested.
+    pub short_writes: u64,
+    ///Number of failed write calls.
+    pub errors: u64,
+    ///Maximum number of bytes that was in buffer.
+    pub high_water_mark: usize,
+}
+
+impl Stats {
+    const fn new() -> Self {
+        Self {
+            bytes_written: 0,
+            writes: 0,
+            short_writes: 0,
+            errors: 0,
+            high_water_mark: 0,
+        }
+    }
+
+    //Records outcome of single write call of `len` bytes.
+    fn record(&mut self, len: usize, result: Result<usize, libc::c_int>) -> Result<usize, libc::c_int> {
+        self.writes += 1;
+        match result {
+            Ok(size) => {
+                self.bytes_written += size as u64;
+                if size < len {
+                    self.short_writes += 1;
+                }
+            },
+            Err(_) => self.errors += 1,
+        }
+
+        result
+    }
+}
+
+//write() may accept only part of data so keep going until everything is written.
+fn write_all<S: RawSink + ?Sized>(sink: &mut S, stats: &mut Stats, data: &[u8]) -> Result<(), WriteError> {
+    let mut written = 0;
+
+    while written < data.len() {
+        let remaining = &data[written..];
+        match stats.record(remaining.len(), sink.raw_write(remaining)) {
+            Ok(0) => return Err(WriteError {
+                errno: 0,
+                written,
+            }),
+            Ok(size) => written += size,
+            //Signal interrupted call before anything is written.
+            #[cfg(not(windows))]
+            Err(libc::EINTR) => continue,
+            Err(errno) => return Err(WriteError {
+                errno,
+                written,
+            }),
+        }
+    }
+
+    Ok(())
+}
+
+///Writes whole data into `fd` directly, without any buffering.
+///
+///This function is async-signal-safe and can be called from signal handler:
+///it only calls `write` in loop until all data is written, retrying on `EINTR`,
+///and doesn't allocate or access any shared state.
+///
+///Note that on error `errno` is modified, which signal handler may need to preserve.
+pub fn write_signal_safe(fd: RawFd, data: &[u8]) -> Result<(), WriteError> {
+    write_all(&mut FdSink::new(fd), &mut Stats::new(), data)
+}
+
+#[cfg(any(unix, windows, target_os = "wasi"))]
+fn write_all_at(fd: RawFd, stats: &mut Stats, data: &[u8], offset: u64) -> Result<(), WriteError> {
+    let mut written = 0;
+
+    while written < data.len() {
+        let remaining = &data[written..];
+        match stats.record(remaining.len(), sys::pwrite(fd, remaining, offset + written as u64)) {
+            Ok(0) => return Err(WriteError {
+                errno: 0,
+                written,
+            }),
+            Ok(size) => written += size,
+            Err(errno) => return Err(WriteError {
+                errno,
+                written,
+            }),
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+///Buffering mode of `FdWriter`.
+pub enum BufferMode {
+    ///Flushes complete lines automatically.
+    ///
+    ///Line is terminated by flush delimiter, which is `\n` by default.
+    ///
+    ///This is default mode.
+    Line,
+    ///Flushes only when buffer is full or when requested explicitly.
+    Full,
+    ///Writes data directly into file descriptor, without buffering.
+    None,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+///Policy of automatic flushing, which is alternative view of `BufferMode` and flush delimiter.
+pub enum AutoFlush {
+    ///Flushes only when buffer is full or when requested explicitly, same as `BufferMode::Full`.
+    Never,
+    ///Flushes everything up to last occurrence of byte, same as `BufferMode::Line` with this flush delimiter.
+    ///
+    ///This is default policy, with `\n` as byte.
+    OnByte(u8),
+    ///Writes every call out directly, same as `BufferMode::None`.
+    Always,
+}
+
+impl AutoFlush {
+    #[inline(always)]
+    //Returns corresponding buffering mode and flush delimiter, if policy has one.
+    const fn into_mode(self) -> (BufferMode, Option<u8>) {
+        match self {
+            AutoFlush::Never => (BufferMode::Full, None),
+            AutoFlush::OnByte(delimiter) => (BufferMode::Line, Some(delimiter)),
+            AutoFlush::Always => (BufferMode::None, None),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+///Policy to guarantee that every flush ends with `\n`, see `FdWriter::set_ensure_newline`.
+pub enum NewlinePolicy {
+    ///Partial line is kept in buffer when it has to be flushed due to lack of space.
+    ///
+    ///Line that doesn't fit into buffer is split into multiple lines, each terminated with `\n`.
+    Hold,
+    ///Partial line is terminated with `\n` when it has to be flushed due to lack of space.
+    Append,
+}
+
+///Wrapper into file descriptor.
+///
+///On Windows it wraps `HANDLE` instead.
+///
+///`N` is capacity of buffer, which must be greater than zero.
+///
+///`S` is destination of data, which is file descriptor by default, but can be any `RawSink`.
+///
+///`B` is storage of buffer, which is inline array of `N` bytes by default.
+///Other storages (e.g. `FdWriterBoxed`) ignore `N`, providing capacity at runtime.
+pub struct FdWriter<const N: usize = BUFFER_CAPACITY, S: RawSink = FdSink, B: Buffer = mem::MaybeUninit<[u8; N]>> {
+    sink: S,
+    len: usize,
+    mode: BufferMode,
+    delimiter: u8,
+    newline: Option<NewlinePolicy>,
+    crlf: bool,
+    threshold: usize,
+    stats: Stats,
+    last_error: Option<WriteError>,
+    buffer: B,
+}
+
+#[cfg(feature = "alloc")]
+///Writer with heap allocated buffer, which capacity is chosen at runtime.
+pub type FdWriterBoxed<S = FdSink> = FdWriter<0, S, alloc::boxed::Box<[mem::MaybeUninit<u8>]>>;
+
+///Writer with caller provided buffer, which capacity is length of slice.
+pub type FdWriterRef<'a, S = FdSink> = FdWriter<0, S, &'a mut [u8]>;
+
+impl FdWriter {
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`
+    pub const fn new(fd: RawFd) -> Self {
+        Self::new_sized(fd)
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`, using specified buffering `mode`
+    pub const fn with_mode(fd: RawFd, mode: BufferMode) -> Self {
+        let mut result = Self::new(fd);
+        result.mode = mode;
+        result
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`, writing every call out directly.
+    ///
+    ///Same as `with_mode(fd, BufferMode::None)`.
+    pub const fn new_unbuffered(fd: RawFd) -> Self {
+        Self::with_mode(fd, BufferMode::None)
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`, using specified auto flush `policy`
+    pub const fn with_auto_flush(fd: RawFd, policy: AutoFlush) -> Self {
+        let mut result = Self::new(fd);
+        let (mode, delimiter) = policy.into_mode();
+        result.mode = mode;
+        if let Some(delimiter) = delimiter {
+            result.delimiter = delimiter;
+        }
+        result
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`, writing every `\n` as `\r\n`
+    pub const fn with_crlf(fd: RawFd) -> Self {
+        let mut result = Self::new(fd);
+        result.crlf = true;
+        result
+    }
+
+    #[cfg(all(feature = "std", unix))]
+    #[inline(always)]
+    ///Creates new instance which owns `fd`, closing it on drop.
+    pub fn from_owned(fd: std::os::fd::OwnedFd) -> OwnedFdWriter {
+        fd.into()
+    }
+
+    #[cfg(all(feature = "std", unix))]
+    #[inline(always)]
+    ///Creates new instance which writes into borrowed `fd`, making sure it doesn't outlive it.
+    pub fn from_borrowed(fd: std::os::fd::BorrowedFd<'_>) -> BorrowedFdWriter<'_> {
+        fd.into()
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into standard input.
+    pub const fn stdin() -> Self {
+        Self::new(sys::STDIN)
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into standard output.
+    pub const fn stdout() -> Self {
+        Self::new(sys::STDOUT)
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into standard error.
+    pub const fn stderr() -> Self {
+        Self::new(sys::STDERR)
+    }
+}
+
+impl<S: RawSink> FdWriter<BUFFER_CAPACITY, S> {
+    #[inline(always)]
+    ///Creates new instance which writes into `sink`
+    pub const fn with_sink(sink: S) -> Self {
+        Self::with_sink_sized(sink)
+    }
+}
+
+impl<const N: usize> FdWriter<N> {
+    #[inline(always)]
+    ///Creates new instance with buffer capacity `N`, which writes into `fd`
+    ///
+    ///Usage: `FdWriter::<64>::new_sized(fd)`
+    pub const fn new_sized(fd: RawFd) -> Self {
+        Self::with_sink_sized(FdSink::new(fd))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FdWriterBoxed {
+    #[inline]
+    ///Creates new instance with heap allocated buffer of `capacity` bytes, which writes into `fd`
+    ///
+    ///Panics if `capacity` is zero.
+    pub fn with_capacity(fd: RawFd, capacity: usize) -> Self {
+        Self::with_sink_capacity(FdSink::new(fd), capacity)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: RawSink> FdWriterBoxed<S> {
+    ///Creates new instance with heap allocated buffer of `capacity` bytes, which writes into `sink`
+    ///
+    ///Panics if `capacity` is zero.
+    pub fn with_sink_capacity(sink: S, capacity: usize) -> Self {
+        assert!(capacity > 0, "FdWriter capacity must be greater than zero");
+        Self::from_buffer(sink, core::iter::repeat_with(mem::MaybeUninit::uninit).take(capacity).collect())
+    }
+
+    #[inline(always)]
+    ///Returns pointer to first element in underlying buffer.
+    pub fn as_ptr(&self) -> *const u8 {
+        Buffer::as_ptr(&self.buffer)
+    }
+
+    #[inline(always)]
+    ///Returns capacity of underlying buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    #[inline(always)]
+    ///Returns number of bytes that can be written before buffer needs to be flushed.
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.len
+    }
+}
+
+impl<'a> FdWriterRef<'a> {
+    #[inline]
+    ///Creates new instance which uses `buffer` as its buffer, writing into `fd`
+    ///
+    ///Panics if `buffer` is empty.
+    pub fn with_buffer(fd: RawFd, buffer: &'a mut [u8]) -> Self {
+        Self::with_sink_buffer(FdSink::new(fd), buffer)
+    }
+}
+
+impl<'a, S: RawSink> FdWriterRef<'a, S> {
+    ///Creates new instance which uses `buffer` as its buffer, writing into `sink`
+    ///
+    ///Panics if `buffer` is empty.
+    pub fn with_sink_buffer(sink: S, buffer: &'a mut [u8]) -> Self {
+        assert!(!buffer.is_empty(), "FdWriter capacity must be greater than zero");
+        Self::from_buffer(sink, buffer)
+    }
+
+    #[inline(always)]
+    ///Returns pointer to first element in underlying buffer.
+    pub fn as_ptr(&self) -> *const u8 {
+        Buffer::as_ptr(&self.buffer)
+    }
+
+    #[inline(always)]
+    ///Returns capacity of underlying buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    #[inline(always)]
+    ///Returns number of bytes that can be written before buffer needs to be flushed.
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.len
+    }
+}
+
+impl<const N: usize, B: Buffer> FdWriter<N, FdSink, B> {
+    #[inline(always)]
+    ///Returns underlying file descriptor.
+    pub const fn fd(&self) -> RawFd {
+        self.sink.fd
+    }
+
+    ///Flushes buffer into current file descriptor and replaces it with `fd`, returning previous one.
+    ///
+    ///Previous file descriptor is not closed.
+    ///If flush fails, error is returned and file descriptor is not replaced, leaving unwritten data in buffer.
+    pub fn replace_fd(&mut self, fd: RawFd) -> Result<RawFd, WriteError> {
+        self.try_flush()?;
+        Ok(mem::replace(&mut self.sink.fd, fd))
+    }
+
+    ///Flushes buffer and returns underlying file descriptor.
+    ///
+    ///Data that failed to be flushed is discarded.
+    pub fn into_fd(self) -> RawFd {
+        self.into_sink().fd
+    }
+}
+
+impl<const N: usize, S: FdBackedSink, B: Buffer> FdWriter<N, S, B> {
+    #[inline]
+    ///Returns whether file descriptor refers to terminal, using `isatty`.
+    ///
+    ///On Windows it checks whether handle is console.
+    pub fn is_terminal(&self) -> bool {
+        sys::isatty(self.sink.fd())
+    }
+
+    #[inline(always)]
+    ///Sets whether to suppress `SIGPIPE`, see `FdSink::set_ignore_sigpipe` for details.
+    pub fn set_ignore_sigpipe(&mut self, enabled: bool) {
+        self.sink.set_ignore_sigpipe(enabled);
+    }
+
+    ///Flushes buffer and synchronizes file content and metadata with storage device using `fsync`.
+    ///
+    ///This is expensive and only meaningful for regular files.
+    ///For pipes and sockets error is returned (typically `EINVAL`).
+    pub fn sync_all(&mut self) -> Result<(), WriteError> {
+        self.try_flush()?;
+        sys::fsync(self.sink.fd()).map_err(WriteError::new)
+    }
+
+    ///Flushes buffer and synchronizes file content with storage device using `fdatasync`.
+    ///
+    ///Unlike `sync_all`, metadata is not synchronized unless required to read data back.
+    ///Falls back to `fsync` where `fdatasync` is not available.
+    ///
+    ///This is expensive and only meaningful for regular files.
+    ///For pipes and sockets error is returned (typically `EINVAL`).
+    pub fn sync_data(&mut self) -> Result<(), WriteError> {
+        self.try_flush()?;
+        sys::fdatasync(self.sink.fd()).map_err(WriteError::new)
+    }
+
+    #[cfg(any(unix, windows, target_os = "wasi"))]
+    ///Writes whole data at `offset` of file using `pwrite`, after flushing buffer.
+    ///
+    ///Current position of file descriptor is not changed, except on Windows where it is updated for synchronous handles.
+    ///
+    ///If buffer cannot be flushed, then nothing from `data` is written.
+    ///Otherwise error's `written` is number of bytes written from `data`.
+    pub fn write_data_at(&mut self, data: &[u8], offset: u64) -> Result<(), WriteError> {
+        self.try_flush()?;
+
+        let result = write_all_at(self.sink.fd(), &mut self.stats, data, offset);
+        if let Err(error) = result {
+            self.last_error = Some(error);
+        }
+
+        result
+    }
+
+    #[cfg(any(unix, windows, target_os = "wasi"))]
+    #[inline]
+    ///Performs single `pwrite` of data at `offset` of file, returning number of bytes written.
+    ///
+    ///Same as `std::os::unix::fs::FileExt::write_at`, buffer is neither flushed nor modified,
+    ///so buffered data is not ordered relative to this write. Use `write_data_at` to flush buffer first.
+    ///
+    ///Current position of file descriptor is not changed, except on Windows where it is updated for synchronous handles.
+    pub fn write_at(&self, data: &[u8], offset: u64) -> Result<usize, WriteError> {
+        sys::pwrite(self.sink.fd(), data, offset).map_err(WriteError::new)
+    }
+}
+
+impl<const N: usize, S: RawSink> FdWriter<N, S> {
+    const CAPACITY_CHECK: () = assert!(N > 0, "FdWriter capacity must be greater than zero");
+
+    ///Creates new instance with buffer capacity `N`, which writes into `sink`
+    ///
+    ///Usage: `FdWriter::<64, _>::with_sink_sized(sink)`
+    pub const fn with_sink_sized(sink: S) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::CAPACITY_CHECK;
+
+        Self::from_buffer(sink, mem::MaybeUninit::uninit())
+    }
+
+    #[inline(always)]
+    ///Returns pointer to first element in underlying buffer.
+    pub const fn as_ptr(&self) -> *const u8 {
+        &self.buffer as *const _ as *const _
+    }
+
+    #[inline(always)]
+    ///Returns capacity of underlying buffer.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline(always)]
+    ///Returns number of bytes that can be written before buffer needs to be flushed.
+    pub const fn remaining(&self) -> usize {
+        N - self.len
+    }
+
+    ///Decomposes writer into sink, number of buffered bytes and buffer, without flushing.
+    ///
+    ///Only first `len` bytes of buffer are buffered data, the rest is zeroed.
+    pub fn into_parts(self) -> (S, usize, [u8; N]) {
+        let this = mem::ManuallyDrop::new(self);
+        let mut buffer = [0; N];
+        buffer[..this.len].copy_from_slice(this.as_slice());
+
+        let sink = unsafe {
+            ptr::read(&this.sink)
+        };
+        (sink, this.len, buffer)
+    }
+}
+
+impl<const N: usize, S: RawSink, B: Buffer> FdWriter<N, S, B> {
+    const fn from_buffer(sink: S, buffer: B) -> Self {
+        Self {
+            sink,
+            len: 0,
+            mode: BufferMode::Line,
+            delimiter: b'\n',
+            newline: None,
+            crlf: false,
+            //Not reached even by full buffer, which is flushed only once there is no room for more data.
+            threshold: usize::MAX,
+            stats: Stats::new(),
+            last_error: None,
+            buffer,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns pointer to first element in underlying buffer.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.buffer.as_mut_ptr()
+    }
+
+    #[inline(always)]
+    ///Returns reference to underlying sink.
+    pub const fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    #[inline(always)]
+    ///Returns mutable reference to underlying sink.
+    ///
+    ///Writing into sink directly bypasses buffer.
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    ///Flushes buffer and returns underlying sink.
+    ///
+    ///Data that failed to be flushed is discarded.
+    pub fn into_sink(self) -> S {
+        let mut this = mem::ManuallyDrop::new(self);
+        this.flush();
+        unsafe {
+            ptr::drop_in_place(&mut this.buffer);
+            ptr::read(&this.sink)
+        }
+    }
+
+    //Returns sink together with buffered data, borrowing them separately.
+    fn split(&mut self) -> (&mut S, &mut Stats, &[u8]) {
+        let buffer = unsafe {
+            slice::from_raw_parts(self.buffer.as_ptr(), self.len)
+        };
+        (&mut self.sink, &mut self.stats, buffer)
+    }
+
+    #[inline(always)]
+    //Returns capacity of underlying buffer, which is available as `capacity` for each storage.
+    fn buffer_capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    #[inline(always)]
+    ///Returns number of bytes currently in buffer.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    ///Returns whether buffer is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    ///Returns immutable slice with current elements
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(self.buffer.as_ptr(), self.len)
+        }
+    }
+
+    #[inline]
+    ///Returns buffered data as text, if it is valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        core::str::from_utf8(self.as_slice()).ok()
+    }
+
+    #[inline(always)]
+    ///Returns current buffering mode.
+    pub const fn mode(&self) -> BufferMode {
+        self.mode
+    }
+
+    #[inline(always)]
+    ///Sets buffering mode.
+    ///
+    ///Data that is already in buffer stays there until next flush.
+    pub fn set_mode(&mut self, mode: BufferMode) {
+        self.mode = mode;
+    }
+
+    #[inline(always)]
+    ///Enables or disables automatic flushing of complete lines, which is enabled by default.
+    ///
+    ///This is shortcut to switch between `BufferMode::Line` and `BufferMode::Full`,
+    ///use `set_auto_flush_policy` to also choose flush byte.
+    ///
+    ///Disabling it avoids syscall per line, improving throughput when writing many lines,
+    ///at the cost of data staying in buffer until it is full or flushed explicitly.
+    pub fn set_auto_flush(&mut self, enabled: bool) {
+        self.mode = match enabled {
+            true => BufferMode::Line,
+            false => BufferMode::Full,
+        };
+    }
+
+    #[inline(always)]
+    ///Returns policy of automatic flushing, which reflects current buffering mode.
+    pub const fn auto_flush(&self) -> AutoFlush {
+        match self.mode {
+            BufferMode::Full => AutoFlush::Never,
+            BufferMode::Line => AutoFlush::OnByte(self.delimiter),
+            BufferMode::None => AutoFlush::Always,
+        }
+    }
+
+    #[inline]
+    ///Sets policy of automatic flushing, switching buffering mode accordingly.
+    ///
+    ///Data that is already in buffer stays there until next flush.
+    pub fn set_auto_flush_policy(&mut self, policy: AutoFlush) {
+        let (mode, delimiter) = policy.into_mode();
+        self.mode = mode;
+        if let Some(delimiter) = delimiter {
+            self.delimiter = delimiter;
+        }
+    }
+
+    #[inline(always)]
+    ///Returns delimiter on which data is flushed automatically, if any.
+    pub const fn flush_delimiter(&self) -> Option<u8> {
+        match self.mode {
+            BufferMode::Line => Some(self.delimiter),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    ///Sets delimiter on which data is flushed automatically.
+    ///
+    ///`Some` switches to `BufferMode::Line`, flushing everything up to last delimiter in written data.
+    ///`None` disables flushing on delimiter, switching to `BufferMode::Full`.
+    pub fn set_flush_delimiter(&mut self, delimiter: Option<u8>) {
+        match delimiter {
+            Some(delimiter) => {
+                self.delimiter = delimiter;
+                self.mode = BufferMode::Line;
+            },
+            None => self.mode = BufferMode::Full,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns number of buffered bytes, reaching which buffer is flushed.
+    pub fn flush_threshold(&self) -> usize {
+        cmp::min(self.threshold, self.buffer_capacity())
+    }
+
+    #[inline]
+    ///Sets number of buffered bytes, reaching which buffer is flushed, which is capacity by default.
+    ///
+    ///By default buffer is flushed only when there is no room for more data.
+    ///
+    ///Lower threshold reduces latency, flushing buffer before it is full, regardless of `BufferMode`.
+    ///Failure of such flush is not returned, but it is available via `last_error`.
+    ///
+    ///Threshold is limited by capacity.
+    pub fn set_flush_threshold(&mut self, bytes: usize) {
+        self.threshold = cmp::min(bytes, self.buffer_capacity());
+    }
+
+    #[inline(always)]
+    ///Returns policy of terminating flushed data with `\n`, if enabled.
+    pub const fn ensure_newline(&self) -> Option<NewlinePolicy> {
+        self.newline
+    }
+
+    ///Sets policy to guarantee that every flush ends with `\n`, which is disabled by default.
+    ///
+    ///When enabled, buffer always reserves one byte for `\n`, and data is always written through buffer.
+    ///Explicit flush terminates partial line with `\n`, while flush due to lack of space follows `policy`.
+    ///
+    ///`BufferMode::None` behaves as `BufferMode::Line`, as otherwise every write would be terminated.
+    ///Note that `write_all_data` writes data directly and is not affected.
+    ///
+    ///Requires capacity to be at least 2.
+    pub fn set_ensure_newline(&mut self, policy: Option<NewlinePolicy>) {
+        assert!(policy.is_none() || self.buffer_capacity() > 1, "Ensuring newline requires capacity of at least 2");
+        self.newline = policy;
+    }
+
+    #[inline(always)]
+    ///Returns whether every `\n` is written as `\r\n`.
+    pub const fn crlf(&self) -> bool {
+        self.crlf
+    }
+
+    ///Sets whether to write every `\n` as `\r\n`, which is disabled by default.
+    ///
+    ///`\r\n` is never split across flushes, as buffer is flushed beforehand when there is no room for both bytes.
+    ///Complete lines are still flushed in `BufferMode::Line`, as long as delimiter is `\n`.
+    ///
+    ///Only data going through buffer is translated: `write_all_data`, `write_atomic` and `write_data_at` write data as it is,
+    ///and `\n` appended when ensuring newline is not translated either.
+    ///
+    ///Requires capacity to be at least 2.
+    pub fn set_crlf(&mut self, enabled: bool) {
+        assert!(!enabled || self.buffer_capacity() > 1, "CRLF mode requires capacity of at least 2");
+        self.crlf = enabled;
+    }
+
+    #[inline(always)]
+    ///Returns total number of bytes written into file descriptor.
+    ///
+    ///Data that is still in buffer is not counted.
+    pub const fn bytes_written(&self) -> u64 {
+        self.stats.bytes_written
+    }
+
+    #[inline]
+    ///Returns number of bytes currently in buffer, same as `len`.
+    pub const fn bytes_buffered(&self) -> usize {
+        self.len()
+    }
+
+    #[inline(always)]
+    ///Resets counter of bytes written into file descriptor.
+    pub fn reset_bytes_written(&mut self) {
+        self.stats.bytes_written = 0;
+    }
+
+    #[inline(always)]
+    ///Returns statistics of writing.
+    pub const fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    #[inline(always)]
+    ///Resets statistics of writing, including number of bytes written.
+    ///
+    ///High water mark is set to number of bytes currently in buffer.
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::new();
+        self.stats.high_water_mark = self.len;
+    }
+
+    #[inline(always)]
+    //Sets number of bytes in buffer, updating high water mark.
+    fn set_len(&mut self, len: usize) {
+        self.len = len;
+        self.stats.high_water_mark = cmp::max(self.stats.high_water_mark, len);
+    }
+
+    #[inline]
+    ///Returns last error that happened during writing, if any.
+    ///
+    ///Useful to inspect outcome of infallible methods, like `flush` or `fmt::Write` implementation.
+    pub fn last_error(&self) -> Option<WriteError> {
+        self.last_error
+    }
+
+    #[inline]
+    ///Returns last error that happened during writing, if any, resetting it.
+    pub fn take_last_error(&mut self) -> Option<WriteError> {
+        self.last_error.take()
+    }
 
-#[cfg(feature = "std")]
-extern crate std;
+    //Removes first `size` bytes from buffer, moving rest of data to the front.
+    fn consume(&mut self, size: usize) {
+        let remaining = self.len - size;
+        if size > 0 && remaining > 0 {
+            unsafe {
+                ptr::copy(self.buffer.as_ptr().add(size), self.as_mut_ptr(), remaining);
+            }
+        }
+        self.len = remaining;
+    }
 
-use core::{slice, cmp, mem, ptr, fmt};
+    //Writes first `end` bytes of buffer.
+    fn flush_until(&mut self, end: usize) -> Result<(), WriteError> {
+        let (sink, stats, buffer) = self.split();
+        let result = write_all(sink, stats, &buffer[..end]);
+        let written = match result {
+            Ok(()) => end,
+            Err(error) => {
+                self.last_error = Some(error);
+                error.written
+            },
+        };
+        self.consume(written);
 
-const BUFFER_CAPACITY: usize = 4096;
+        result
+    }
 
-///Wrapper into file descriptor.
-pub struct FdWriter {
-    fd: libc::c_int,
-    len: u16,
-    buffer: mem::MaybeUninit<[u8; BUFFER_CAPACITY]>,
-}
+    //Writes data directly into fd, bypassing buffer and recording outcome.
+    fn write_out(&mut self, data: &[u8]) -> Result<(), WriteError> {
+        let result = write_all(&mut self.sink, &mut self.stats, data);
+        if let Err(error) = result {
+            self.last_error = Some(error);
+        }
 
-impl FdWriter {
-    ///Creates new instance which writes into `fd`
-    pub const fn new(fd: libc::c_int) -> Self {
-        Self {
-            fd,
-            len: 0,
-            buffer: mem::MaybeUninit::uninit(),
+        result
+    }
+
+    //Appends `\n` unless buffer already ends with it.
+    //When ensuring newline, buffer always has room for it.
+    fn terminate_line(&mut self) {
+        if self.len < self.buffer_capacity() && self.as_slice().last() != Some(&b'\n') {
+            unsafe {
+                self.as_mut_ptr().add(self.len).write(b'\n');
+            }
+            self.set_len(self.len + 1);
         }
     }
 
-    #[inline(always)]
-    ///Returns pointer to first element in underlying buffer.
-    pub const fn as_ptr(&self) -> *const u8 {
-        &self.buffer as *const _ as *const _
+    fn inner_flush(&mut self) -> Result<(), WriteError> {
+        if self.newline.is_some() {
+            self.terminate_line();
+        }
+
+        self.flush_until(self.len)
     }
 
-    #[inline(always)]
-    ///Returns pointer to first element in underlying buffer.
-    pub fn as_mut_ptr(&mut self) -> *mut u8 {
-        self.buffer.as_mut_ptr() as *mut _ as *mut _
+    //Flushes buffer, that has no room for data, when ensuring newline.
+    fn flush_full_line(&mut self) -> Result<(), WriteError> {
+        let end = match self.newline {
+            Some(NewlinePolicy::Hold) => self.as_slice().iter().rposition(|byte| *byte == b'\n'),
+            _ => None,
+        };
+
+        match end {
+            Some(idx) => self.flush_until(idx + 1),
+            None => self.inner_flush(),
+        }
     }
 
-    #[inline]
-    ///Returns immutable slice with current elements
-    pub fn as_slice(&self) -> &[u8] {
-        unsafe {
-            slice::from_raw_parts(self.as_ptr(), self.len as _)
+    //Writes data directly into fd, bypassing buffer.
+    fn write_direct(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        match self.write_out(data) {
+            Ok(()) => Ok(data.len()),
+            Err(error) => match error.written {
+                0 => Err(error),
+                written => Ok(written),
+            },
         }
     }
 
-    fn inner_flush(&mut self) {
-        let text = unsafe {
-            core::str::from_utf8_unchecked(self.as_slice())
-        };
-        unsafe {
-            libc::write(self.fd.into(), text.as_ptr() as *const _, text.len() as _);
+    ///Flushes buffer, clearing buffer.
+    ///
+    ///Returns error if underlying `write` fails, in which case data that is not written yet remains in buffer.
+    ///
+    ///`EINTR` is not considered an error and `write` is retried instead.
+    pub fn try_flush(&mut self) -> Result<(), WriteError> {
+        if self.len > 0 {
+            self.inner_flush()
+        } else {
+            Ok(())
         }
+    }
+
+    #[inline(always)]
+    ///Discards buffered data without writing it.
+    ///
+    ///Unlike `flush`, nothing is written into file descriptor, buffered data is lost.
+    pub fn clear(&mut self) {
         self.len = 0;
     }
 
+    #[inline]
     ///Flushes buffer, clearing buffer.
+    ///
+    ///Errors are ignored, use `try_flush` to handle them.
     pub fn flush(&mut self) {
-        if self.len > 0 {
-            self.inner_flush();
+        let _ = self.try_flush();
+    }
+
+    #[inline(always)]
+    ///Returns guard, which flushes writer at the end of scope.
+    ///
+    ///Use `FlushGuard::finish` to observe flush errors, otherwise they are ignored on drop.
+    pub fn flush_guard(&mut self) -> FlushGuard<'_, N, S, B> {
+        FlushGuard::new(self)
+    }
+
+    #[inline]
+    //Returns capacity available for data, keeping room for `\n` when ensuring newline.
+    fn data_capacity(&self) -> usize {
+        match self.newline {
+            Some(_) => self.buffer_capacity() - 1,
+            None => self.buffer_capacity(),
         }
     }
 
     #[inline]
     fn copy_data<'a>(&mut self, data: &'a [u8]) -> &'a [u8] {
-        let write_len = cmp::min(BUFFER_CAPACITY.saturating_sub(self.len as _), data.len());
+        let write_len = cmp::min(self.data_capacity().saturating_sub(self.len), data.len());
         unsafe {
-            ptr::copy_nonoverlapping(data.as_ptr(), self.as_mut_ptr().add(self.len as _), write_len);
+            ptr::copy_nonoverlapping(data.as_ptr(), self.as_mut_ptr().add(self.len), write_len);
         }
-        self.len += write_len as u16;
+        self.set_len(self.len + write_len);
         &data[write_len..]
     }
 
+    //Writes buffer followed by `data` within single syscall, returning number of bytes written from `data`.
+    //
+    //Keeps going until buffer is drained, but `data` may be written only partially.
+    fn flush_vectored(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        let mut written = 0;
+
+        loop {
+            let (sink, stats, buffer) = self.split();
+            let error = match stats.record(buffer.len() + data.len(), sink.raw_write_vectored(&[buffer, data])) {
+                Ok(0) => WriteError {
+                    errno: 0,
+                    written,
+                },
+                Ok(size) if size < self.len => {
+                    self.consume(size);
+                    written += size;
+                    continue;
+                },
+                Ok(size) => {
+                    let data_written = size - self.len;
+                    self.len = 0;
+                    break Ok(data_written);
+                },
+                #[cfg(not(windows))]
+                Err(libc::EINTR) => continue,
+                Err(errno) => WriteError {
+                    errno,
+                    written,
+                },
+            };
+
+            self.last_error = Some(error);
+            break Err(error);
+        }
+    }
+
+    //Copies data into buffer, flushing it whenever it is full, when ensuring newline.
+    fn buffer_lines(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        let mut remaining = data;
+
+        loop {
+            remaining = self.copy_data(remaining);
+
+            if remaining.is_empty() {
+                break Ok(data.len());
+            } else if let Err(error) = self.flush_full_line() {
+                break match data.len() - remaining.len() {
+                    0 => Err(error),
+                    written => Ok(written),
+                };
+            }
+        }
+    }
+
+    //Flushes buffer once flush threshold is reached, same as when buffer is full.
+    fn flush_on_threshold(&mut self) {
+        if self.len > 0 && self.len >= self.threshold {
+            let _ = match self.newline {
+                Some(_) => self.flush_full_line(),
+                None => self.inner_flush(),
+            };
+        }
+    }
+
+    //Buffers data, flushing buffer once flush threshold is reached.
+    fn buffer_data(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        let result = self.store_data(data);
+        self.flush_on_threshold();
+        result
+    }
+
+    //Copies data into buffer, if it fits.
+    //Otherwise writes data together with buffer, buffering only remainder that is smaller than buffer.
+    fn store_data(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        if self.newline.is_some() {
+            return self.buffer_lines(data);
+        }
+
+        if data.len() <= self.buffer_capacity() - self.len {
+            self.copy_data(data);
+            return Ok(data.len());
+        }
+
+        let written = match self.len {
+            0 => 0,
+            _ => self.flush_vectored(data)?,
+        };
+        let remaining = &data[written..];
+
+        if remaining.len() < self.buffer_capacity() {
+            self.copy_data(remaining);
+            Ok(data.len())
+        } else {
+            match self.write_direct(remaining) {
+                Ok(size) => Ok(written + size),
+                Err(error) if written == 0 => Err(error),
+                Err(_) => Ok(written),
+            }
+        }
+    }
+
+    ///Writes data unto buffer, returning number of bytes written.
+    ///
+    ///Depending on `BufferMode`:
+    ///
+    ///- `Line` - Flushing complete lines (i.e. everything up to last delimiter) automatically.
+    ///- `Full` - Flushing only when buffer is full.
+    ///- `None` - Flushing buffer, if not empty, and writing `data` directly.
+    ///
+    ///If flush, required to make room for data, fails, then returns number of bytes written so
+    ///far or error if nothing is written (e.g. `EAGAIN` on non-blocking file descriptor).
+    ///
+    ///Failure of automatic flush on delimiter is not returned as data is already in buffer, but it
+    ///is still available via `last_error`.
+    ///
+    ///Empty `data` is no-op.
+    pub fn try_write_data(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        if data.is_empty() {
+            Ok(0)
+        } else if self.crlf {
+            self.write_crlf_lines(data)
+        } else {
+            self.write_lines(data)
+        }
+    }
+
+    //Writes non-empty data according to `BufferMode`.
+    fn write_lines(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        match self.mode {
+            BufferMode::Line => (),
+            BufferMode::Full => return self.buffer_data(data),
+            BufferMode::None if self.newline.is_some() => (),
+            BufferMode::None => {
+                self.try_flush()?;
+                return self.write_direct(data);
+            },
+        }
+
+        let (lines, tail) = match data.iter().rposition(|byte| *byte == self.delimiter) {
+            Some(idx) => data.split_at(idx + 1),
+            None => return self.buffer_data(data),
+        };
+
+        let written = self.buffer_data(lines)?;
+        if written < lines.len() {
+            return Ok(written);
+        }
+
+        let _ = self.try_flush();
+
+        match self.buffer_data(tail) {
+            Ok(size) => Ok(written + size),
+            Err(_) => Ok(written),
+        }
+    }
+
+    //Writes `\r\n` into buffer at once, flushing buffer beforehand if there is no room for both bytes.
+    fn buffer_crlf(&mut self) -> Result<(), WriteError> {
+        if self.len + 2 > self.data_capacity() {
+            self.try_flush()?;
+        }
+
+        if self.len + 2 > self.data_capacity() {
+            //Only possible when ensuring newline with capacity of 2
+            return self.write_out(b"\r\n");
+        }
+
+        unsafe {
+            ptr::copy_nonoverlapping(b"\r\n".as_ptr(), self.as_mut_ptr().add(self.len), 2);
+        }
+        self.set_len(self.len + 2);
+
+        match self.mode {
+            BufferMode::Line | BufferMode::None if self.delimiter == b'\n' => {
+                let _ = self.try_flush();
+            },
+            _ => self.flush_on_threshold(),
+        }
+        Ok(())
+    }
+
+    //Writes non-empty data according to `BufferMode`, replacing every `\n` with `\r\n`.
+    //
+    //Returns number of bytes written from `data`, where `\r\n` counts as single byte.
+    fn write_crlf_lines(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        let mut written = 0;
+
+        for line in data.split_inclusive(|byte| *byte == b'\n') {
+            let text = line.strip_suffix(b"\n").unwrap_or(line);
+            let result = match text.is_empty() {
+                true => Ok(0),
+                false => self.write_lines(text),
+            };
+            let result = match result {
+                Ok(size) if size == text.len() && text.len() < line.len() => match self.buffer_crlf() {
+                    Ok(()) => Ok(size + 1),
+                    Err(error) if size == 0 => Err(error),
+                    Err(_) => Ok(size),
+                },
+                result => result,
+            };
+
+            match result {
+                Ok(size) => {
+                    written += size;
+                    if size < line.len() {
+                        break;
+                    }
+                },
+                Err(error) if written == 0 => return Err(error),
+                Err(_) => break,
+            }
+        }
+
+        Ok(written)
+    }
+
+    #[inline]
     ///Writes data unto buffer.
     ///
-    ///Flushing if it ends with `\n` automatically
-    pub fn write_data(&mut self, mut data: &[u8]) {
-        loop {
-            data = self.copy_data(data);
+    ///Flushing according to `BufferMode`, see `try_write_data` for details.
+    ///
+    ///Errors are ignored, use `try_write_data` or `last_error` to handle them.
+    pub fn write_data(&mut self, data: &[u8]) {
+        let _ = self.try_write_data(data);
+    }
 
-            if data.len() == 0 {
-                break;
-            } else {
+    #[inline]
+    ///Writes single byte unto buffer.
+    ///
+    ///Flushing according to `BufferMode`, same as `write_data`, but without going through slice when byte fits into buffer.
+    ///
+    ///Errors are ignored, use `last_error` to handle them.
+    pub fn write_byte(&mut self, byte: u8) {
+        let (buffered, line) = match self.mode {
+            BufferMode::Line => (true, true),
+            BufferMode::Full => (true, false),
+            BufferMode::None => (self.newline.is_some(), true),
+        };
+
+        if buffered && self.len < self.data_capacity() && !(self.crlf && byte == b'\n') {
+            unsafe {
+                self.as_mut_ptr().add(self.len).write(byte);
+            }
+            self.set_len(self.len + 1);
+
+            if line && byte == self.delimiter {
                 self.flush();
+            } else {
+                self.flush_on_threshold();
+            }
+        } else {
+            self.write_data(&[byte]);
+        }
+    }
+
+    #[inline]
+    ///Writes bytes of C string, without terminating NUL, unto buffer.
+    ///
+    ///Flushing according to `BufferMode`, same as `write_data`.
+    pub fn write_cstr(&mut self, text: &core::ffi::CStr) {
+        self.write_data(text.to_bytes());
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    ///Writes bytes of OS string unto buffer, as they are.
+    ///
+    ///On unix these are raw bytes, which may be not valid UTF-8, while on Windows it is WTF-8 encoding.
+    ///
+    ///Flushing according to `BufferMode`, same as `write_data`.
+    pub fn write_os_str(&mut self, text: &std::ffi::OsStr) {
+        self.write_data(text.as_encoded_bytes());
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    ///Writes bytes of path unto buffer, as they are.
+    ///
+    ///See `write_os_str` for details.
+    pub fn write_path(&mut self, path: &std::path::Path) {
+        self.write_os_str(path.as_os_str());
+    }
+
+    ///Writes items separated by `sep` unto buffer.
+    ///
+    ///Flushing according to `BufferMode`, see `try_write_data` for details.
+    ///
+    ///Errors are ignored, use `last_error` to handle them.
+    pub fn write_join<I: IntoIterator<Item = T>, T: fmt::Display>(&mut self, iter: I, sep: &str) {
+        let mut iter = iter.into_iter();
+
+        if let Some(item) = iter.next() {
+            let _ = fmt::Write::write_fmt(self, format_args!("{}", item));
+        }
+
+        for item in iter {
+            self.write_data(sep.as_bytes());
+            let _ = fmt::Write::write_fmt(self, format_args!("{}", item));
+        }
+    }
+
+    ///Writes bytes items separated by `sep` unto buffer.
+    ///
+    ///Flushing according to `BufferMode`, see `try_write_data` for details.
+    ///
+    ///Errors are ignored, use `last_error` to handle them.
+    pub fn write_join_bytes<I: IntoIterator<Item = T>, T: AsRef<[u8]>>(&mut self, iter: I, sep: &[u8]) {
+        let mut iter = iter.into_iter();
+
+        if let Some(item) = iter.next() {
+            self.write_data(item.as_ref());
+        }
+
+        for item in iter {
+            self.write_data(sep);
+            self.write_data(item.as_ref());
+        }
+    }
+
+    ///Writes whole data directly into file descriptor, after flushing buffer.
+    ///
+    ///Unlike `try_write_data`, it doesn't return until either all data is written or error happens.
+    ///
+    ///If buffer cannot be flushed, then nothing from `data` is written.
+    ///Otherwise error's `written` is number of bytes written from `data`.
+    pub fn write_all_data(&mut self, data: &[u8]) -> Result<(), WriteError> {
+        self.try_flush()?;
+        self.write_out(data)
+    }
+
+    ///Writes data directly within single write, after flushing buffer.
+    ///
+    ///Writes of up to `PIPE_BUF` bytes into pipe are atomic, hence data is never interleaved with other writers.
+    ///Returns `false` without writing anything, if `data` is larger than `PIPE_BUF`.
+    ///
+    ///Write is never split, hence if only part of `data` is written, which can happen for non-pipe file descriptors,
+    ///error with `errno` of `0` is returned, having number of written bytes.
+    ///Remaining data is not written, as it would no longer be atomic.
+    pub fn write_atomic(&mut self, data: &[u8]) -> Result<bool, WriteError> {
+        if data.len() > PIPE_BUF {
+            return Ok(false);
+        }
+
+        self.try_flush()?;
+
+        let written = loop {
+            match self.stats.record(data.len(), self.sink.raw_write(data)) {
+                Ok(size) => break size,
+                #[cfg(not(windows))]
+                Err(libc::EINTR) => continue,
+                Err(errno) => {
+                    let error = WriteError::new(errno);
+                    self.last_error = Some(error);
+                    return Err(error);
+                },
             }
+        };
+
+        if written < data.len() {
+            let error = WriteError {
+                errno: 0,
+                written,
+            };
+            self.last_error = Some(error);
+            return Err(error);
         }
 
-        if self.as_slice()[self.len as usize - 1] == b'\n' {
+        Ok(true)
+    }
+
+    //Appends `\n` to already written data, flushing unless buffering fully.
+    fn finish_line(&mut self) {
+        if self.buffer_data(b"\n").is_ok() && self.mode != BufferMode::Full {
             self.flush();
         }
     }
+
+    ///Writes data followed by `\n` unto buffer.
+    ///
+    ///Unlike `write_data`, line is flushed only once, after `\n` is written, unless in `BufferMode::Full`.
+    ///
+    ///Errors are ignored, use `last_error` to handle them.
+    pub fn write_line(&mut self, data: &[u8]) {
+        if let Ok(written) = self.buffer_data(data) {
+            if written == data.len() {
+                self.finish_line();
+            }
+        }
+    }
+
+    ///Writes formatted arguments followed by `\n` unto buffer.
+    ///
+    ///Similarly to `write_line`, line is flushed only once, after `\n` is written, unless in `BufferMode::Full`.
+    ///
+    ///Errors are ignored, use `last_error` to handle them.
+    pub fn write_fmt_line(&mut self, args: fmt::Arguments<'_>) {
+        let mode = mem::replace(&mut self.mode, BufferMode::Full);
+        let result = fmt::Write::write_fmt(self, args);
+        self.mode = mode;
+
+        if result.is_ok() {
+            self.finish_line();
+        }
+    }
 }
 
-impl fmt::Write for FdWriter {
-    #[inline]
+//Escaped beginning of buffered data.
+struct Preview<'a>(&'a [u8]);
+
+impl fmt::Debug for Preview<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const LIMIT: usize = 32;
+
+        match self.0.get(..LIMIT) {
+            Some(preview) if self.0.len() > LIMIT => write!(fmt, "\"{}\"...", preview.escape_ascii()),
+            _ => write!(fmt, "\"{}\"", self.0.escape_ascii()),
+        }
+    }
+}
+
+impl<const N: usize, S: RawSink + fmt::Debug, B: Buffer> fmt::Debug for FdWriter<N, S, B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("FdWriter").field("sink", &self.sink).field("buffered", &self.len).field("capacity", &self.buffer_capacity()).field("preview", &Preview(self.as_slice())).finish()
+    }
+}
+
+impl<const N: usize, S: RawSink, B: Buffer> fmt::Write for FdWriter<N, S, B> {
+    ///Writes text unto buffer, failing if any error happens during writing.
+    ///
+    ///As `fmt::Error` carries no information, actual error is available via `last_error`.
     fn write_str(&mut self, text: &str) -> fmt::Result {
-        self.write_data(text.as_bytes());
+        let previous = self.last_error.take();
 
-        Ok(())
+        match self.try_write_data(text.as_bytes()) {
+            Ok(written) if written == text.len() && self.last_error.is_none() => {
+                self.last_error = previous;
+                Ok(())
+            },
+            _ => Err(fmt::Error),
+        }
     }
 }
 
 #[cfg(feature = "std")]
-impl std::io::Write for FdWriter {
+impl<const N: usize, S: RawSink, B: Buffer> std::io::Write for FdWriter<N, S, B> {
+    #[inline(always)]
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.write_data(buf);
-        Ok(buf.len())
+        self.try_write_data(buf).map_err(Into::into)
+    }
+
+    ///Copies slices into buffer, if all of them fit.
+    ///
+    ///Otherwise flushes buffer and writes slices directly using single vectored write.
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        let total = bufs.iter().fold(0usize, |total, buf| total.saturating_add(buf.len()));
+
+        if (total <= self.buffer_capacity() - self.len && self.mode != BufferMode::None) || self.newline.is_some() || self.crlf {
+            //Stops at first short write, as following slices cannot be accepted without it.
+            let mut accepted = 0;
+            for buf in bufs {
+                match self.try_write_data(buf) {
+                    Ok(size) => {
+                        accepted += size;
+                        if size < buf.len() {
+                            break;
+                        }
+                    },
+                    Err(error) if accepted == 0 => return Err(error.into()),
+                    Err(_) => break,
+                }
+            }
+            return Ok(accepted);
+        }
+
+        self.try_flush()?;
+
+        let mut slices: [&[u8]; sys::IOV_LIMIT] = [&[]; sys::IOV_LIMIT];
+        let len = cmp::min(bufs.len(), sys::IOV_LIMIT);
+        for (slice, buf) in slices.iter_mut().zip(bufs) {
+            *slice = buf;
+        }
+
+        let total = slices[..len].iter().map(|slice| slice.len()).sum();
+        match self.stats.record(total, self.sink.raw_write_vectored(&slices[..len])) {
+            Ok(size) => Ok(size),
+            Err(errno) => {
+                let error = WriteError::new(errno);
+                self.last_error = Some(error);
+                Err(error.into())
+            }
+        }
     }
 
     #[inline(always)]
     fn flush(&mut self) -> std::io::Result<()> {
-        self.flush();
-        Ok(())
+        self.try_flush().map_err(Into::into)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize, S: FdBackedSink, B: Buffer> std::io::Seek for FdWriter<N, S, B> {
+    ///Flushes buffer, so that buffered data is written at current position, and then moves position of file descriptor.
+    ///
+    ///Nothing is moved if flush fails.
+    ///Seeking is only possible for regular files, otherwise error is returned (`ESPIPE` for pipes and sockets).
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.try_flush()?;
+        sys::seek(self.sink.fd(), pos).map_err(std::io::Error::from_raw_os_error)
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize, S: FdBackedSink, B: Buffer> std::os::fd::AsRawFd for FdWriter<N, S, B> {
+    #[inline(always)]
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.sink.fd()
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize> std::os::fd::FromRawFd for FdWriter<N> {
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`
+    ///
+    ///Note that `FdWriter` never closes `fd`, use `OwnedFdWriter` to transfer ownership.
+    unsafe fn from_raw_fd(fd: std::os::fd::RawFd) -> Self {
+        Self::new_sized(fd)
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize, B: Buffer> std::os::fd::IntoRawFd for FdWriter<N, FdSink, B> {
+    #[inline(always)]
+    ///Flushes buffer and returns file descriptor, same as `into_fd`.
+    fn into_raw_fd(self) -> std::os::fd::RawFd {
+        self.into_fd()
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<T: std::os::fd::AsRawFd, const N: usize> From<&T> for FdWriter<N> {
+    #[inline(always)]
+    ///Creates new instance which writes into file descriptor of `source`
+    ///
+    ///`source` retains ownership and must outlive writer, use `BorrowedFdWriter` to enforce it.
+    fn from(source: &T) -> Self {
+        Self::new_sized(source.as_raw_fd())
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+impl<const N: usize, S: FdBackedSink, B: Buffer> std::os::windows::io::AsRawHandle for FdWriter<N, S, B> {
+    #[inline(always)]
+    fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+        sys::resolve(self.sink.fd())
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+impl<const N: usize> std::os::windows::io::FromRawHandle for FdWriter<N> {
+    #[inline(always)]
+    ///Creates new instance which writes into `handle`
+    ///
+    ///Note that `FdWriter` never closes `handle`, use `OwnedFdWriter` to transfer ownership.
+    unsafe fn from_raw_handle(handle: std::os::windows::io::RawHandle) -> Self {
+        Self::new_sized(handle)
     }
 }
 
-impl Drop for FdWriter {
+impl<const N: usize, S: RawSink, B: Buffer> Drop for FdWriter<N, S, B> {
     #[inline]
     fn drop(&mut self) {
         self.flush();
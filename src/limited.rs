@@ -0,0 +1,100 @@
+use core::{cmp, fmt};
+
+///Writer that writes at most `limit` bytes into `W`, truncating anything beyond it.
+///
+///Once limit is reached, `fmt::Write` fails with `fmt::Error` and `std::io::Write::write` returns `Ok(0)`.
+pub struct LimitedWriter<W> {
+    inner: W,
+    limit: usize,
+    written: usize,
+}
+
+impl<W> LimitedWriter<W> {
+    #[inline(always)]
+    ///Creates new instance which writes at most `limit` bytes into `inner`
+    pub const fn new(inner: W, limit: usize) -> Self {
+        Self {
+            inner,
+            limit,
+            written: 0,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns number of bytes that can be written before limit is reached.
+    pub const fn bytes_remaining(&self) -> usize {
+        self.limit - self.written
+    }
+
+    #[inline(always)]
+    ///Returns whether limit is reached, in which case nothing can be written anymore.
+    pub const fn limit_reached(&self) -> bool {
+        self.written >= self.limit
+    }
+
+    #[inline(always)]
+    ///Returns reference to underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline(always)]
+    ///Returns mutable reference to underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline(always)]
+    ///Returns underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for LimitedWriter<W> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("LimitedWriter").field("inner", &self.inner).field("limit", &self.limit).field("written", &self.written).finish()
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for LimitedWriter<W> {
+    ///Writes as much of `text` as fits within limit, failing if `text` is truncated.
+    ///
+    ///Text is truncated on character boundary.
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let mut len = cmp::min(text.len(), self.bytes_remaining());
+        while !text.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        if len > 0 {
+            self.inner.write_str(&text[..len])?;
+            self.written += len;
+        }
+
+        match len == text.len() {
+            true => Ok(()),
+            false => Err(fmt::Error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> std::io::Write for LimitedWriter<W> {
+    ///Writes as much of `buf` as fits within limit, returning `Ok(0)` once limit is reached.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = cmp::min(buf.len(), self.bytes_remaining());
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let written = self.inner.write(&buf[..len])?;
+        self.written += written;
+        Ok(written)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,95 @@
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{sys, FdWriterSync, RawFd, BUFFER_CAPACITY};
+
+static STDERR: FdWriterLogger = FdWriterLogger::new(sys::STDERR, log::LevelFilter::Off);
+
+#[inline]
+fn level_filter(level: usize) -> log::LevelFilter {
+    match level {
+        0 => log::LevelFilter::Off,
+        1 => log::LevelFilter::Error,
+        2 => log::LevelFilter::Warn,
+        3 => log::LevelFilter::Info,
+        4 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    }
+}
+
+///`log::Log` implementation writing records into file descriptor.
+///
+///Each record is written as `[LEVEL target] message` line, which is flushed immediately.
+pub struct FdWriterLogger<const N: usize = BUFFER_CAPACITY> {
+    writer: FdWriterSync<N>,
+    level: AtomicUsize,
+}
+
+impl FdWriterLogger {
+    #[inline(always)]
+    ///Creates new instance which writes records up to `level` into `fd`
+    pub const fn new(fd: RawFd, level: log::LevelFilter) -> Self {
+        Self::new_sized(fd, level)
+    }
+
+    ///Installs static logger, which writes records up to `level` into stderr, as global logger.
+    ///
+    ///Fails if global logger is already set, in which case level is not changed.
+    pub fn init_stderr(level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+        log::set_logger(&STDERR)?;
+        STDERR.set_level(level);
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl<const N: usize> FdWriterLogger<N> {
+    #[inline(always)]
+    ///Creates new instance with buffer capacity `N`, which writes records up to `level` into `fd`
+    pub const fn new_sized(fd: RawFd, level: log::LevelFilter) -> Self {
+        Self {
+            writer: FdWriterSync::new_sized(fd),
+            level: AtomicUsize::new(level as usize),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns maximum level of records that are written.
+    pub fn level(&self) -> log::LevelFilter {
+        level_filter(self.level.load(Ordering::Relaxed))
+    }
+
+    #[inline(always)]
+    ///Sets maximum level of records that are written.
+    ///
+    ///Note that `log` macros also filter records by `log::max_level()`.
+    pub fn set_level(&self, level: log::LevelFilter) {
+        self.level.store(level as usize, Ordering::Relaxed);
+    }
+}
+
+impl<const N: usize> fmt::Debug for FdWriterLogger<N> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("FdWriterLogger").field("level", &self.level()).finish_non_exhaustive()
+    }
+}
+
+impl<const N: usize> log::Log for FdWriterLogger<N> {
+    #[inline(always)]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= self.level()
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            let mut writer = self.writer.lock();
+            writer.write_fmt_line(format_args!("[{} {}] {}", record.level(), record.target(), record.args()));
+            writer.flush();
+        }
+    }
+
+    #[inline(always)]
+    fn flush(&self) {
+        self.writer.lock().flush();
+    }
+}
@@ -0,0 +1,55 @@
+#[macro_export]
+///Writes formatted arguments into shared standard output writer.
+///
+///Output is flushed according to buffering mode, i.e. on every complete line.
+///Errors are ignored.
+macro_rules! fd_print {
+    ($($arg:tt)*) => {{
+        let _ = ::core::fmt::Write::write_fmt(&mut $crate::stdout(), ::core::format_args!($($arg)*));
+    }};
+}
+
+#[macro_export]
+///Writes formatted arguments followed by `\n` into shared standard output writer, flushing it.
+///
+///Errors are ignored.
+macro_rules! fd_println {
+    () => {{
+        let mut writer = $crate::stdout().lock();
+        writer.write_line(b"");
+        writer.flush();
+    }};
+    ($($arg:tt)*) => {{
+        let mut writer = $crate::stdout().lock();
+        writer.write_fmt_line(::core::format_args!($($arg)*));
+        writer.flush();
+    }};
+}
+
+#[macro_export]
+///Writes formatted arguments into shared standard error writer.
+///
+///Output is flushed according to buffering mode, i.e. on every complete line.
+///Errors are ignored.
+macro_rules! fd_eprint {
+    ($($arg:tt)*) => {{
+        let _ = ::core::fmt::Write::write_fmt(&mut $crate::stderr(), ::core::format_args!($($arg)*));
+    }};
+}
+
+#[macro_export]
+///Writes formatted arguments followed by `\n` into shared standard error writer, flushing it.
+///
+///Errors are ignored.
+macro_rules! fd_eprintln {
+    () => {{
+        let mut writer = $crate::stderr().lock();
+        writer.write_line(b"");
+        writer.flush();
+    }};
+    ($($arg:tt)*) => {{
+        let mut writer = $crate::stderr().lock();
+        writer.write_fmt_line(::core::format_args!($($arg)*));
+        writer.flush();
+    }};
+}
@@ -0,0 +1,240 @@
+use core::{fmt, mem, ops};
+
+use crate::{sys, FdBackedSink, FdSink, FdWriter, RawFd, RawSink, WriteError, BUFFER_CAPACITY};
+
+///Sink of `OwnedFdWriter`.
+///
+///Unlike `FdSink`, it cannot be copied or created from arbitrary file descriptor,
+///hence file descriptor cannot be replaced via `OwnedFdWriter`.
+pub struct OwnedFdSink {
+    inner: FdSink,
+}
+
+impl OwnedFdSink {
+    #[inline(always)]
+    const fn new(fd: RawFd) -> Self {
+        Self {
+            inner: FdSink::new(fd),
+        }
+    }
+}
+
+impl FdBackedSink for OwnedFdSink {
+    #[inline(always)]
+    fn fd(&self) -> RawFd {
+        self.inner.fd
+    }
+
+    #[inline(always)]
+    fn set_ignore_sigpipe(&mut self, enabled: bool) {
+        self.inner.set_ignore_sigpipe(enabled);
+    }
+}
+
+impl RawSink for OwnedFdSink {
+    #[inline(always)]
+    fn raw_write(&mut self, data: &[u8]) -> Result<usize, libc::c_int> {
+        self.inner.raw_write(data)
+    }
+
+    #[inline(always)]
+    fn raw_write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+        self.inner.raw_write_vectored(bufs)
+    }
+}
+
+impl fmt::Debug for OwnedFdSink {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("OwnedFdSink").field("fd", &self.inner.fd).finish()
+    }
+}
+
+///Writer that owns file descriptor, closing it on drop.
+///
+///All writing is done through underlying `FdWriter`, accessible via `Deref`.
+pub struct OwnedFdWriter<const N: usize = BUFFER_CAPACITY> {
+    inner: FdWriter<N, OwnedFdSink>,
+}
+
+impl OwnedFdWriter {
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`, closing it on drop.
+    ///
+    ///# Safety
+    ///
+    ///`fd` must be open file descriptor, owned by caller and not closed by anyone else.
+    pub const unsafe fn new(fd: RawFd) -> Self {
+        Self::new_sized(fd)
+    }
+}
+
+impl<const N: usize> OwnedFdWriter<N> {
+    #[inline(always)]
+    ///Creates new instance with buffer capacity `N`, which writes into `fd`, closing it on drop.
+    ///
+    ///# Safety
+    ///
+    ///`fd` must be open file descriptor, owned by caller and not closed by anyone else.
+    pub const unsafe fn new_sized(fd: RawFd) -> Self {
+        Self {
+            inner: FdWriter::with_sink_sized(OwnedFdSink::new(fd)),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns underlying file descriptor.
+    pub const fn fd(&self) -> RawFd {
+        self.inner.sink.inner.fd
+    }
+
+    ///Flushes buffer and closes file descriptor.
+    ///
+    ///Unlike dropping, it allows to observe errors.
+    ///File descriptor is closed even if flush fails, in which case flush error is returned.
+    pub fn close(self) -> Result<(), WriteError> {
+        let mut this = mem::ManuallyDrop::new(self);
+        let flush = this.inner.try_flush();
+        this.inner.len = 0;
+        let close = sys::close(this.inner.sink.inner.fd).map_err(WriteError::new);
+
+        flush.and(close)
+    }
+
+    ///Flushes buffer and returns file descriptor without closing it, transferring ownership to caller.
+    ///
+    ///Data that failed to be flushed is discarded.
+    pub fn into_raw_fd(self) -> RawFd {
+        let mut this = mem::ManuallyDrop::new(self);
+        this.inner.flush();
+        this.inner.len = 0;
+        this.inner.sink.inner.fd
+    }
+}
+
+impl<const N: usize> ops::Deref for OwnedFdWriter<N> {
+    type Target = FdWriter<N, OwnedFdSink>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<const N: usize> ops::DerefMut for OwnedFdWriter<N> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<const N: usize> fmt::Debug for OwnedFdWriter<N> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("OwnedFdWriter").field("fd", &self.inner.sink.inner.fd).field("buffered", &self.inner.len).finish()
+    }
+}
+
+impl<const N: usize> fmt::Write for OwnedFdWriter<N> {
+    #[inline(always)]
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        fmt::Write::write_str(&mut self.inner, text)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> std::io::Write for OwnedFdWriter<N> {
+    #[inline(always)]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        std::io::Write::write(&mut self.inner, buf)
+    }
+
+    #[inline(always)]
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        std::io::Write::write_vectored(&mut self.inner, bufs)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::Write::flush(&mut self.inner)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> std::io::Seek for OwnedFdWriter<N> {
+    #[inline(always)]
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        std::io::Seek::seek(&mut self.inner, pos)
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize> std::os::fd::AsRawFd for OwnedFdWriter<N> {
+    #[inline(always)]
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.inner.sink.inner.fd
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize> std::os::fd::AsFd for OwnedFdWriter<N> {
+    #[inline(always)]
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        //File descriptor is owned, hence it is valid as long as writer exists.
+        unsafe {
+            std::os::fd::BorrowedFd::borrow_raw(self.inner.sink.inner.fd)
+        }
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize> std::os::fd::FromRawFd for OwnedFdWriter<N> {
+    #[inline(always)]
+    unsafe fn from_raw_fd(fd: std::os::fd::RawFd) -> Self {
+        Self::new_sized(fd)
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize> std::os::fd::IntoRawFd for OwnedFdWriter<N> {
+    #[inline(always)]
+    fn into_raw_fd(self) -> std::os::fd::RawFd {
+        OwnedFdWriter::into_raw_fd(self)
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize> From<OwnedFdWriter<N>> for std::os::fd::OwnedFd {
+    #[inline]
+    fn from(writer: OwnedFdWriter<N>) -> Self {
+        unsafe {
+            std::os::fd::FromRawFd::from_raw_fd(writer.into_raw_fd())
+        }
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize> From<std::os::fd::OwnedFd> for OwnedFdWriter<N> {
+    #[inline]
+    fn from(fd: std::os::fd::OwnedFd) -> Self {
+        unsafe {
+            Self::new_sized(std::os::fd::IntoRawFd::into_raw_fd(fd))
+        }
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl<const N: usize> From<std::fs::File> for OwnedFdWriter<N> {
+    #[inline]
+    fn from(file: std::fs::File) -> Self {
+        std::os::fd::OwnedFd::from(file).into()
+    }
+}
+
+impl<const N: usize> Drop for OwnedFdWriter<N> {
+    fn drop(&mut self) {
+        self.inner.flush();
+        //Whatever failed to be written must not be attempted after fd is closed.
+        self.inner.len = 0;
+        let _ = sys::close(self.inner.sink.inner.fd);
+    }
+}
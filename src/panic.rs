@@ -0,0 +1,62 @@
+use core::fmt::{self, Write};
+use core::panic::PanicInfo;
+
+use crate::{BufferMode, FdWriter, RawFd};
+
+const PANIC_BUFFER_CAPACITY: usize = 512;
+
+//Writer that silently truncates output which doesn't fit into buffer.
+struct Truncated<'a> {
+    writer: &'a mut FdWriter<PANIC_BUFFER_CAPACITY>,
+}
+
+impl fmt::Write for Truncated<'_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        //Keep room for `\n`
+        let remaining = self.writer.remaining().saturating_sub(1);
+        if text.len() <= remaining {
+            self.writer.copy_data(text.as_bytes());
+            Ok(())
+        } else {
+            let mut end = remaining;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.writer.copy_data(&text.as_bytes()[..end]);
+            //Stop formatting as there is no more room.
+            Err(fmt::Error)
+        }
+    }
+}
+
+///Writes panic message and location into `fd`, followed by `\n`.
+///
+///Intended to be used within `#[panic_handler]`: output is formatted into buffer on stack,
+///without allocation, and written with single flush.
+///Output that doesn't fit into buffer of 512 bytes is truncated.
+///
+///```rust,ignore
+///#![no_std]
+///
+///#[panic_handler]
+///fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
+///    fd_writer::write_panic_info(fd_writer::FdWriter::stderr().fd(), info);
+///    unsafe {
+///        libc::abort()
+///    }
+///}
+///```
+#[inline]
+pub fn write_panic_info(fd: RawFd, info: &PanicInfo<'_>) {
+    write_truncated(fd, info);
+}
+
+//Writes `value` followed by `\n` with single flush, truncating it to fit into buffer.
+pub(crate) fn write_truncated(fd: RawFd, value: &dyn fmt::Display) {
+    let mut writer = FdWriter::<PANIC_BUFFER_CAPACITY>::new_sized(fd);
+    writer.set_mode(BufferMode::Full);
+
+    let _ = write!(Truncated { writer: &mut writer }, "{}", value);
+    writer.copy_data(b"\n");
+    writer.flush();
+}
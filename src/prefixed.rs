@@ -0,0 +1,166 @@
+use core::fmt;
+
+///Source of prefix, written by `PrefixedWriter` at the start of every line.
+pub trait LinePrefix {
+    #[inline(always)]
+    ///Called once per line, right before its prefix is written.
+    ///
+    ///Allows prefix to be computed lazily, e.g. to take timestamp.
+    fn start_line(&mut self) {}
+
+    ///Returns prefix of current line.
+    ///
+    ///When written through `fmt::Write`, prefix must be valid UTF-8.
+    fn as_bytes(&self) -> &[u8];
+}
+
+impl LinePrefix for &'static [u8] {
+    #[inline(always)]
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl LinePrefix for &'static str {
+    #[inline(always)]
+    fn as_bytes(&self) -> &[u8] {
+        str::as_bytes(self)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum State {
+    //Next byte starts new line
+    LineStart,
+    //Number of prefix bytes already written
+    Prefix(usize),
+    //Prefix of current line is written
+    Line,
+}
+
+///Writer that writes prefix at the start of every line.
+///
+///Prefix is written lazily, only when first byte of new line is written.
+pub struct PrefixedWriter<W, P = &'static [u8]> {
+    inner: W,
+    prefix: P,
+    state: State,
+}
+
+impl<W, P: LinePrefix> PrefixedWriter<W, P> {
+    #[inline(always)]
+    ///Creates new instance which writes into `inner`, prepending `prefix` to every line
+    pub const fn new(inner: W, prefix: P) -> Self {
+        Self {
+            inner,
+            prefix,
+            state: State::LineStart,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns reference to prefix source.
+    pub fn prefix(&self) -> &P {
+        &self.prefix
+    }
+
+    #[inline(always)]
+    ///Returns reference to underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline(always)]
+    ///Returns mutable reference to underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    #[inline(always)]
+    ///Returns underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    //Writes rest of prefix if line is not started yet, using `write` that returns number of bytes written.
+    fn write_prefix<E>(&mut self, mut write: impl FnMut(&mut W, &[u8]) -> Result<usize, E>) -> Result<(), E> {
+        let mut written = match self.state {
+            State::Line => return Ok(()),
+            State::Prefix(written) => written,
+            State::LineStart => {
+                self.prefix.start_line();
+                0
+            },
+        };
+
+        let prefix = self.prefix.as_bytes();
+        while written < prefix.len() {
+            self.state = State::Prefix(written);
+            written += write(&mut self.inner, &prefix[written..])?;
+        }
+        self.state = State::Line;
+
+        Ok(())
+    }
+
+    //Updates state after `line` (or part of it) is written.
+    fn end_line(&mut self, line: &[u8]) {
+        if line.last() == Some(&b'\n') {
+            self.state = State::LineStart;
+        }
+    }
+}
+
+impl<W: fmt::Debug, P: fmt::Debug> fmt::Debug for PrefixedWriter<W, P> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("PrefixedWriter").field("inner", &self.inner).field("prefix", &self.prefix).finish()
+    }
+}
+
+impl<W: fmt::Write, P: LinePrefix> fmt::Write for PrefixedWriter<W, P> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for line in text.split_inclusive('\n') {
+            self.write_prefix(|inner, prefix| match core::str::from_utf8(prefix) {
+                Ok(prefix) => inner.write_str(prefix).map(|_| prefix.len()),
+                //Prefix is not UTF-8 or `io::Write` stopped in the middle of character
+                Err(_) => Err(fmt::Error),
+            })?;
+            self.inner.write_str(line)?;
+            self.end_line(line.as_bytes());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write, P: LinePrefix> std::io::Write for PrefixedWriter<W, P> {
+    ///Writes prefix, if needed, followed by at most one line of `buf`.
+    ///
+    ///Returned number of bytes never includes prefix.
+    ///If prefix fails to be written, then error is returned and written part of prefix is not repeated on next write.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        self.write_prefix(|inner, prefix| match inner.write(prefix) {
+            Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+            result => result,
+        })?;
+
+        let line = match buf.iter().position(|byte| *byte == b'\n') {
+            Some(idx) => &buf[..=idx],
+            None => buf,
+        };
+        let written = self.inner.write(line)?;
+        self.end_line(&line[..written]);
+
+        Ok(written)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,164 @@
+use core::fmt;
+
+use crate::{sys, RawFd};
+
+///Destination into which `FdWriter` writes its buffer.
+///
+///Errors are reported as `errno` codes, which are carried by `WriteError`.
+pub trait RawSink {
+    ///Performs single write, returning number of bytes written or error code.
+    ///
+    ///Returning `Ok(0)` for non-empty `data` is treated as error.
+    ///`EINTR` is retried by writer.
+    fn raw_write(&mut self, data: &[u8]) -> Result<usize, libc::c_int>;
+
+    #[inline]
+    ///Performs single write of multiple buffers, returning number of bytes written or error code.
+    ///
+    ///By default writes only first non-empty buffer.
+    fn raw_write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+        match bufs.iter().find(|buf| !buf.is_empty()) {
+            Some(buf) => self.raw_write(buf),
+            None => Ok(0),
+        }
+    }
+}
+
+impl<S: RawSink + ?Sized> RawSink for &mut S {
+    #[inline(always)]
+    fn raw_write(&mut self, data: &[u8]) -> Result<usize, libc::c_int> {
+        S::raw_write(self, data)
+    }
+
+    #[inline(always)]
+    fn raw_write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+        S::raw_write_vectored(self, bufs)
+    }
+}
+
+#[cfg(feature = "std")]
+impl RawSink for std::vec::Vec<u8> {
+    #[inline]
+    fn raw_write(&mut self, data: &[u8]) -> Result<usize, libc::c_int> {
+        self.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    #[inline]
+    fn raw_write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+        let mut written = 0;
+        for buf in bufs {
+            self.extend_from_slice(buf);
+            written += buf.len();
+        }
+        Ok(written)
+    }
+}
+
+///Sink that writes into file descriptor.
+///
+///Enables file descriptor specific methods of `FdWriter`, like `sync_all`.
+pub trait FdBackedSink: RawSink {
+    ///Returns underlying file descriptor.
+    fn fd(&self) -> RawFd;
+
+    ///Sets whether to suppress `SIGPIPE`, see `FdSink::set_ignore_sigpipe` for details.
+    fn set_ignore_sigpipe(&mut self, enabled: bool);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+///File descriptor sink, used by `FdWriter` by default.
+pub struct FdSink {
+    pub(crate) fd: RawFd,
+    nosignal: bool,
+}
+
+//HANDLE can be used from any thread.
+#[cfg(windows)]
+unsafe impl Send for FdSink {}
+#[cfg(windows)]
+unsafe impl Sync for FdSink {}
+
+impl FdSink {
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`
+    pub const fn new(fd: RawFd) -> Self {
+        Self {
+            fd,
+            nosignal: false,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns underlying file descriptor.
+    pub const fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    #[inline(always)]
+    ///Returns whether `SIGPIPE` is suppressed.
+    pub const fn ignore_sigpipe(&self) -> bool {
+        self.nosignal
+    }
+
+    #[inline(always)]
+    ///Sets whether to suppress `SIGPIPE`, which is disabled by default.
+    ///
+    ///When enabled, sockets are written via `send` with `MSG_NOSIGNAL`, failing with `EPIPE` instead of raising signal.
+    ///Vectored writes are not used in this case.
+    ///
+    ///Other file descriptors (e.g. pipes) cannot suppress signal per write and rely on process-wide disposition of `SIGPIPE`,
+    ///which needs to be ignored to receive `EPIPE`.
+    ///It is disabled automatically once `fd` turns out not to be socket.
+    ///
+    ///Has no effect where `MSG_NOSIGNAL` is not available.
+    pub fn set_ignore_sigpipe(&mut self, enabled: bool) {
+        self.nosignal = enabled;
+    }
+}
+
+impl fmt::Debug for FdSink {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.fd, fmt)
+    }
+}
+
+impl FdBackedSink for FdSink {
+    #[inline(always)]
+    fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    #[inline(always)]
+    fn set_ignore_sigpipe(&mut self, enabled: bool) {
+        self.nosignal = enabled;
+    }
+}
+
+impl RawSink for FdSink {
+    #[inline(always)]
+    fn raw_write(&mut self, data: &[u8]) -> Result<usize, libc::c_int> {
+        if self.nosignal {
+            match sys::send_nosignal(self.fd, data) {
+                #[cfg(not(windows))]
+                Err(libc::ENOTSOCK) => self.nosignal = false,
+                result => return result,
+            }
+        }
+
+        sys::write(self.fd, data)
+    }
+
+    #[inline]
+    fn raw_write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+        if self.nosignal {
+            return match bufs.iter().find(|buf| !buf.is_empty()) {
+                Some(buf) => self.raw_write(buf),
+                None => Ok(0),
+            };
+        }
+
+        sys::writev(self.fd, bufs)
+    }
+}
@@ -0,0 +1,225 @@
+use core::{fmt, ops};
+
+use crate::{FdWriter, RawFd, BUFFER_CAPACITY};
+
+#[cfg(feature = "std")]
+type Lock<T> = std::sync::Mutex<T>;
+#[cfg(feature = "std")]
+type Guard<'a, T> = std::sync::MutexGuard<'a, T>;
+
+#[cfg(not(feature = "std"))]
+type Lock<T> = spin::Lock<T>;
+#[cfg(not(feature = "std"))]
+type Guard<'a, T> = spin::Guard<'a, T>;
+
+#[cfg(not(feature = "std"))]
+mod spin {
+    use core::cell::UnsafeCell;
+    use core::sync::atomic::{AtomicBool, Ordering};
+    use core::ops;
+
+    pub struct Lock<T> {
+        locked: AtomicBool,
+        value: UnsafeCell<T>,
+    }
+
+    unsafe impl<T: Send> Sync for Lock<T> {}
+
+    impl<T> Lock<T> {
+        #[inline(always)]
+        pub const fn new(value: T) -> Self {
+            Self {
+                locked: AtomicBool::new(false),
+                value: UnsafeCell::new(value),
+            }
+        }
+
+        #[inline]
+        pub fn lock(&self) -> Guard<'_, T> {
+            while self.locked.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+                core::hint::spin_loop();
+            }
+
+            Guard {
+                lock: self,
+            }
+        }
+    }
+
+    pub struct Guard<'a, T> {
+        lock: &'a Lock<T>,
+    }
+
+    impl<T> ops::Deref for Guard<'_, T> {
+        type Target = T;
+
+        #[inline(always)]
+        fn deref(&self) -> &Self::Target {
+            unsafe {
+                &*self.lock.value.get()
+            }
+        }
+    }
+
+    impl<T> ops::DerefMut for Guard<'_, T> {
+        #[inline(always)]
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            unsafe {
+                &mut *self.lock.value.get()
+            }
+        }
+    }
+
+    impl<T> Drop for Guard<'_, T> {
+        #[inline(always)]
+        fn drop(&mut self) {
+            self.lock.locked.store(false, Ordering::Release);
+        }
+    }
+}
+
+///Writer that can be shared between threads, suitable for use in `static`.
+///
+///Underlying `FdWriter` is protected by `std::sync::Mutex` with `std` feature, and by spinlock otherwise.
+///
+///`fmt::Write` and, with `std`, `io::Write` are implemented for `&FdWriterSync`, holding lock
+///for whole `write_fmt` or `write_all` call, hence output of concurrent `write!` invocations is never interleaved.
+pub struct FdWriterSync<const N: usize = BUFFER_CAPACITY> {
+    inner: Lock<FdWriter<N>>,
+}
+
+impl FdWriterSync {
+    #[inline(always)]
+    ///Creates new instance which writes into `fd`
+    pub const fn new(fd: RawFd) -> Self {
+        Self::new_sized(fd)
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into stdout
+    pub const fn stdout() -> Self {
+        Self::new(crate::sys::STDOUT)
+    }
+
+    #[inline(always)]
+    ///Creates new instance which writes into stderr
+    pub const fn stderr() -> Self {
+        Self::new(crate::sys::STDERR)
+    }
+}
+
+impl<const N: usize> FdWriterSync<N> {
+    #[inline(always)]
+    ///Creates new instance with buffer capacity `N`, which writes into `fd`
+    pub const fn new_sized(fd: RawFd) -> Self {
+        Self {
+            inner: Lock::new(FdWriter::new_sized(fd)),
+        }
+    }
+
+    #[inline]
+    ///Acquires exclusive access to underlying writer, blocking until it is available.
+    pub fn lock(&self) -> FdWriterGuard<'_, N> {
+        #[cfg(feature = "std")]
+        let guard = self.inner.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        #[cfg(not(feature = "std"))]
+        let guard = self.inner.lock();
+
+        FdWriterGuard {
+            guard,
+        }
+    }
+}
+
+static STDOUT: FdWriterSync = FdWriterSync::stdout();
+static STDERR: FdWriterSync = FdWriterSync::stderr();
+
+#[inline(always)]
+///Returns shared writer into standard output, used by `fd_print!` and `fd_println!`.
+///
+///Being `static` it is never dropped, hence data that is not flushed is lost on exit.
+pub fn stdout() -> &'static FdWriterSync {
+    &STDOUT
+}
+
+#[inline(always)]
+///Returns shared writer into standard error, used by `fd_eprint!` and `fd_eprintln!`.
+///
+///Being `static` it is never dropped, hence data that is not flushed is lost on exit.
+pub fn stderr() -> &'static FdWriterSync {
+    &STDERR
+}
+
+impl<const N: usize> fmt::Debug for FdWriterSync<N> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("FdWriterSync").finish_non_exhaustive()
+    }
+}
+
+impl<const N: usize> fmt::Write for &FdWriterSync<N> {
+    #[inline(always)]
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        fmt::Write::write_str(&mut *self.lock(), text)
+    }
+
+    #[inline(always)]
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        fmt::Write::write_fmt(&mut *self.lock(), args)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> std::io::Write for &FdWriterSync<N> {
+    #[inline(always)]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        std::io::Write::write(&mut *self.lock(), buf)
+    }
+
+    #[inline(always)]
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        std::io::Write::write_vectored(&mut *self.lock(), bufs)
+    }
+
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        std::io::Write::write_all(&mut *self.lock(), buf)
+    }
+
+    #[inline(always)]
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> std::io::Result<()> {
+        std::io::Write::write_fmt(&mut *self.lock(), args)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::Write::flush(&mut *self.lock())
+    }
+}
+
+///Exclusive access to `FdWriter` of `FdWriterSync`, released on drop.
+pub struct FdWriterGuard<'a, const N: usize = BUFFER_CAPACITY> {
+    guard: Guard<'a, FdWriter<N>>,
+}
+
+impl<const N: usize> ops::Deref for FdWriterGuard<'_, N> {
+    type Target = FdWriter<N>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<const N: usize> ops::DerefMut for FdWriterGuard<'_, N> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<const N: usize> fmt::Debug for FdWriterGuard<'_, N> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.guard, fmt)
+    }
+}
@@ -0,0 +1,25 @@
+//! Platform specific syscalls.
+
+#[cfg(not(any(windows, target_os = "wasi", all(feature = "rustix", unix))))]
+mod posix;
+#[cfg(not(any(windows, target_os = "wasi", all(feature = "rustix", unix))))]
+pub use posix::*;
+
+#[cfg(all(feature = "rustix", unix))]
+mod rustix;
+#[cfg(all(feature = "rustix", unix))]
+pub use self::rustix::*;
+
+#[cfg(windows)]
+mod windows;
+#[cfg(windows)]
+pub use windows::*;
+
+#[cfg(target_os = "wasi")]
+mod wasi;
+#[cfg(target_os = "wasi")]
+pub use wasi::*;
+
+///Maximum number of buffers written by single vectored write.
+#[allow(dead_code)]
+pub const IOV_LIMIT: usize = 16;
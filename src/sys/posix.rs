@@ -0,0 +1,225 @@
+//! POSIX syscalls via libc.
+
+///File descriptor.
+pub type RawFd = libc::c_int;
+
+///Standard input.
+pub const STDIN: RawFd = libc::STDIN_FILENO;
+///Standard output.
+pub const STDOUT: RawFd = libc::STDOUT_FILENO;
+///Standard error.
+pub const STDERR: RawFd = libc::STDERR_FILENO;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten", target_os = "fuchsia", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd"))]
+///Maximum size of atomic write into pipe.
+pub const PIPE_BUF: usize = libc::PIPE_BUF;
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten", target_os = "fuchsia", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd")))]
+///Maximum size of atomic write into pipe, as minimum required by POSIX.
+pub const PIPE_BUF: usize = 512;
+
+#[cfg(any(target_os = "linux", target_os = "emscripten", target_os = "fuchsia", target_os = "redox", target_os = "dragonfly", target_os = "hurd", target_os = "l4re"))]
+use libc::__errno_location as errno_location;
+#[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd", target_os = "cygwin", target_os = "nuttx", target_env = "newlib"))]
+use libc::__errno as errno_location;
+#[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+use libc::__error as errno_location;
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+use libc::___errno as errno_location;
+#[cfg(target_os = "haiku")]
+use libc::_errnop as errno_location;
+#[cfg(target_os = "aix")]
+use libc::_Errno as errno_location;
+#[cfg(target_os = "nto")]
+use libc::__get_errno_ptr as errno_location;
+
+#[cfg(not(any(target_os = "linux", target_os = "emscripten", target_os = "fuchsia", target_os = "redox", target_os = "dragonfly", target_os = "hurd", target_os = "l4re", target_os = "android", target_os = "netbsd", target_os = "openbsd", target_os = "cygwin", target_os = "nuttx", target_env = "newlib", target_vendor = "apple", target_os = "freebsd", target_os = "solaris", target_os = "illumos", target_os = "haiku", target_os = "aix", target_os = "nto", target_os = "vxworks")))]
+//Location of `errno` is unknown, so any error is reported as `EIO`.
+unsafe fn errno_location() -> *const libc::c_int {
+    &libc::EIO
+}
+
+#[cfg(not(target_os = "vxworks"))]
+#[inline]
+pub fn errno() -> libc::c_int {
+    unsafe {
+        *errno_location()
+    }
+}
+
+#[cfg(target_os = "vxworks")]
+#[inline]
+pub fn errno() -> libc::c_int {
+    unsafe {
+        libc::errnoGet()
+    }
+}
+
+//`EINTR` is retried, as signal interrupted call before anything is written.
+fn retry<F: FnMut() -> isize>(mut syscall: F) -> Result<usize, libc::c_int> {
+    loop {
+        let result = syscall();
+
+        if result >= 0 {
+            break Ok(result as usize);
+        }
+
+        match errno() {
+            libc::EINTR => continue,
+            errno => break Err(errno),
+        }
+    }
+}
+
+///Performs single `write`, returning number of bytes written or `errno`.
+pub fn write(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    retry(|| unsafe {
+        libc::write(fd, data.as_ptr() as *const _, data.len() as _) as isize
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "fuchsia", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd", target_os = "solaris", target_os = "illumos"))]
+///Performs single `send` with `MSG_NOSIGNAL`, returning number of bytes written or `errno`.
+///
+///Fails with `ENOTSOCK` if `fd` is not socket.
+pub fn send_nosignal(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    retry(|| unsafe {
+        libc::send(fd, data.as_ptr() as *const _, data.len() as _, libc::MSG_NOSIGNAL) as isize
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "fuchsia", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd", target_os = "solaris", target_os = "illumos")))]
+///Performs single `write`, as `MSG_NOSIGNAL` is not available.
+pub fn send_nosignal(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    write(fd, data)
+}
+
+#[cfg(unix)]
+///Performs single `writev`, returning number of bytes written or `errno`.
+///
+///Number of buffers is limited to `IOV_LIMIT`.
+pub fn writev(fd: RawFd, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+    let len = core::cmp::min(bufs.len(), super::IOV_LIMIT);
+    let bufs = core::array::from_fn::<_, { super::IOV_LIMIT }, _>(|idx| match bufs.get(idx) {
+        Some(buf) => libc::iovec {
+            iov_base: buf.as_ptr() as *mut _,
+            iov_len: buf.len(),
+        },
+        None => libc::iovec {
+            iov_base: core::ptr::null_mut(),
+            iov_len: 0,
+        },
+    });
+
+    retry(|| unsafe {
+        libc::writev(fd, bufs.as_ptr(), len as _)
+    })
+}
+
+#[cfg(not(unix))]
+///Writes first non-empty buffer, as `writev` is not available.
+pub fn writev(fd: RawFd, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+    match bufs.iter().find(|buf| !buf.is_empty()) {
+        Some(buf) => write(fd, buf),
+        None => Ok(0),
+    }
+}
+
+#[cfg(unix)]
+///Performs single `pwrite` at `offset`, returning number of bytes written or `errno`.
+pub fn pwrite(fd: RawFd, data: &[u8], offset: u64) -> Result<usize, libc::c_int> {
+    retry(|| unsafe {
+        libc::pwrite(fd, data.as_ptr() as *const _, data.len() as _, offset as _) as isize
+    })
+}
+
+///Synchronizes file content and metadata, returning `errno` on error.
+pub fn fsync(fd: RawFd) -> Result<(), libc::c_int> {
+    retry(|| unsafe {
+        libc::fsync(fd) as isize
+    }).map(|_| ())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten", target_os = "fuchsia", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd", target_os = "solaris", target_os = "illumos"))]
+///Synchronizes file content, returning `errno` on error.
+pub fn fdatasync(fd: RawFd) -> Result<(), libc::c_int> {
+    retry(|| unsafe {
+        libc::fdatasync(fd) as isize
+    }).map(|_| ())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten", target_os = "fuchsia", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd", target_os = "solaris", target_os = "illumos")))]
+///Synchronizes file content via `fsync`, as `fdatasync` is not available.
+pub fn fdatasync(fd: RawFd) -> Result<(), libc::c_int> {
+    fsync(fd)
+}
+
+///Closes file descriptor, returning `errno` on error.
+pub fn close(fd: RawFd) -> Result<(), libc::c_int> {
+    match unsafe { libc::close(fd) } {
+        0 => Ok(()),
+        _ => Err(errno()),
+    }
+}
+
+#[cfg(feature = "tokio")]
+///Puts file descriptor into non-blocking mode using `fcntl`, returning previous flags.
+pub fn set_nonblocking(fd: RawFd) -> Result<libc::c_int, libc::c_int> {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags == -1 {
+        return Err(errno());
+    }
+
+    set_flags(fd, flags | libc::O_NONBLOCK).map(|_| flags)
+}
+
+#[cfg(feature = "tokio")]
+///Sets file status flags using `fcntl`, e.g. to restore ones returned by `set_nonblocking`.
+pub fn set_flags(fd: RawFd, flags: libc::c_int) -> Result<(), libc::c_int> {
+    match unsafe { libc::fcntl(fd, libc::F_SETFL, flags) } {
+        -1 => Err(errno()),
+        _ => Ok(()),
+    }
+}
+
+///Returns whether file descriptor refers to terminal.
+pub fn isatty(fd: RawFd) -> bool {
+    unsafe {
+        libc::isatty(fd) == 1
+    }
+}
+
+#[cfg(feature = "std")]
+///Performs `lseek`, returning new offset or `errno`.
+pub fn seek(fd: RawFd, pos: std::io::SeekFrom) -> Result<u64, libc::c_int> {
+    let (offset, whence) = match pos {
+        std::io::SeekFrom::Start(offset) => (offset as i64, libc::SEEK_SET),
+        std::io::SeekFrom::Current(offset) => (offset, libc::SEEK_CUR),
+        std::io::SeekFrom::End(offset) => (offset, libc::SEEK_END),
+    };
+
+    match unsafe { libc::lseek(fd, offset as _, whence) } {
+        -1 => Err(errno()),
+        offset => Ok(offset as u64),
+    }
+}
+
+fn clock_gettime(clock: libc::clockid_t) -> core::time::Duration {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe {
+        libc::clock_gettime(clock, &mut time);
+    }
+    core::time::Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+///Returns time of monotonic clock.
+pub fn monotonic() -> core::time::Duration {
+    clock_gettime(libc::CLOCK_MONOTONIC)
+}
+
+///Returns time of wall clock since UNIX epoch.
+pub fn realtime() -> core::time::Duration {
+    clock_gettime(libc::CLOCK_REALTIME)
+}
@@ -0,0 +1,167 @@
+//! POSIX syscalls via rustix.
+
+use rustix::fd::BorrowedFd;
+use rustix::io::{Errno, IoSlice};
+
+///File descriptor.
+pub type RawFd = libc::c_int;
+
+///Standard input.
+pub const STDIN: RawFd = libc::STDIN_FILENO;
+///Standard output.
+pub const STDOUT: RawFd = libc::STDOUT_FILENO;
+///Standard error.
+pub const STDERR: RawFd = libc::STDERR_FILENO;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten", target_os = "fuchsia", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd"))]
+///Maximum size of atomic write into pipe.
+pub const PIPE_BUF: usize = libc::PIPE_BUF;
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten", target_os = "fuchsia", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd")))]
+///Maximum size of atomic write into pipe, as minimum required by POSIX.
+pub const PIPE_BUF: usize = 512;
+
+//Negative `fd` is rejected with `EBADF`, same as libc does, as `BorrowedFd` must not be `-1`.
+//Validity of other values is up to caller, same as with libc.
+#[inline(always)]
+fn borrow(fd: RawFd) -> Result<BorrowedFd<'static>, libc::c_int> {
+    match fd {
+        0.. => Ok(unsafe {
+            BorrowedFd::borrow_raw(fd)
+        }),
+        _ => Err(libc::EBADF),
+    }
+}
+
+//`EINTR` is retried, as signal interrupted call before anything is written.
+fn retry<T, F: FnMut() -> rustix::io::Result<T>>(mut syscall: F) -> Result<T, libc::c_int> {
+    loop {
+        match syscall() {
+            Ok(result) => break Ok(result),
+            Err(Errno::INTR) => continue,
+            Err(errno) => break Err(errno.raw_os_error()),
+        }
+    }
+}
+
+///Performs single `write`, returning number of bytes written or `errno`.
+pub fn write(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    let fd = borrow(fd)?;
+    retry(|| rustix::io::write(fd, data))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "fuchsia", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd", target_os = "solaris", target_os = "illumos"))]
+///Performs single `send` with `MSG_NOSIGNAL`, returning number of bytes written or `errno`.
+///
+///Fails with `ENOTSOCK` if `fd` is not socket.
+pub fn send_nosignal(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    let fd = borrow(fd)?;
+    retry(|| rustix::net::send(fd, data, rustix::net::SendFlags::NOSIGNAL))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "fuchsia", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd", target_os = "solaris", target_os = "illumos")))]
+///Performs single `write`, as `MSG_NOSIGNAL` is not available.
+pub fn send_nosignal(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    write(fd, data)
+}
+
+///Performs single `writev`, returning number of bytes written or `errno`.
+///
+///Number of buffers is limited to `IOV_LIMIT`.
+pub fn writev(fd: RawFd, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+    let len = core::cmp::min(bufs.len(), super::IOV_LIMIT);
+    let bufs = core::array::from_fn::<_, { super::IOV_LIMIT }, _>(|idx| match bufs.get(idx) {
+        Some(buf) => IoSlice::new(buf),
+        None => IoSlice::new(&[]),
+    });
+
+    let fd = borrow(fd)?;
+    retry(|| rustix::io::writev(fd, &bufs[..len]))
+}
+
+///Performs single `pwrite` at `offset`, returning number of bytes written or `errno`.
+pub fn pwrite(fd: RawFd, data: &[u8], offset: u64) -> Result<usize, libc::c_int> {
+    let fd = borrow(fd)?;
+    retry(|| rustix::io::pwrite(fd, data, offset))
+}
+
+///Synchronizes file content and metadata, returning `errno` on error.
+pub fn fsync(fd: RawFd) -> Result<(), libc::c_int> {
+    let fd = borrow(fd)?;
+    retry(|| rustix::fs::fsync(fd))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten", target_os = "fuchsia", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "solaris", target_os = "illumos"))]
+///Synchronizes file content, returning `errno` on error.
+pub fn fdatasync(fd: RawFd) -> Result<(), libc::c_int> {
+    let fd = borrow(fd)?;
+    retry(|| rustix::fs::fdatasync(fd))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten", target_os = "fuchsia", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "solaris", target_os = "illumos")))]
+///Synchronizes file content via `fsync`, as `fdatasync` is not available.
+pub fn fdatasync(fd: RawFd) -> Result<(), libc::c_int> {
+    fsync(fd)
+}
+
+///Closes file descriptor, returning `errno` on error.
+pub fn close(fd: RawFd) -> Result<(), libc::c_int> {
+    if fd < 0 {
+        return Err(libc::EBADF);
+    }
+
+    unsafe {
+        rustix::io::try_close(fd)
+    }.map_err(Errno::raw_os_error)
+}
+
+#[cfg(feature = "tokio")]
+///Puts file descriptor into non-blocking mode using `fcntl`, returning previous flags.
+pub fn set_nonblocking(fd: RawFd) -> Result<libc::c_int, libc::c_int> {
+    let fd = borrow(fd)?;
+    let flags = retry(|| rustix::fs::fcntl_getfl(fd))?;
+    retry(|| rustix::fs::fcntl_setfl(fd, flags | rustix::fs::OFlags::NONBLOCK))?;
+    Ok(flags.bits() as libc::c_int)
+}
+
+#[cfg(feature = "tokio")]
+///Sets file status flags using `fcntl`, e.g. to restore ones returned by `set_nonblocking`.
+pub fn set_flags(fd: RawFd, flags: libc::c_int) -> Result<(), libc::c_int> {
+    let fd = borrow(fd)?;
+    let flags = rustix::fs::OFlags::from_bits_retain(flags as _);
+    retry(|| rustix::fs::fcntl_setfl(fd, flags))
+}
+
+///Returns whether file descriptor refers to terminal.
+pub fn isatty(fd: RawFd) -> bool {
+    match borrow(fd) {
+        Ok(fd) => rustix::termios::isatty(fd),
+        Err(_) => false,
+    }
+}
+
+#[cfg(feature = "std")]
+///Performs `lseek`, returning new offset or `errno`.
+pub fn seek(fd: RawFd, pos: std::io::SeekFrom) -> Result<u64, libc::c_int> {
+    let pos = match pos {
+        std::io::SeekFrom::Start(offset) => rustix::fs::SeekFrom::Start(offset),
+        std::io::SeekFrom::Current(offset) => rustix::fs::SeekFrom::Current(offset),
+        std::io::SeekFrom::End(offset) => rustix::fs::SeekFrom::End(offset),
+    };
+
+    rustix::fs::seek(borrow(fd)?, pos).map_err(Errno::raw_os_error)
+}
+
+fn clock_gettime(clock: rustix::time::ClockId) -> core::time::Duration {
+    let time = rustix::time::clock_gettime(clock);
+    core::time::Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+///Returns time of monotonic clock.
+pub fn monotonic() -> core::time::Duration {
+    clock_gettime(rustix::time::ClockId::Monotonic)
+}
+
+///Returns time of wall clock since UNIX epoch.
+pub fn realtime() -> core::time::Duration {
+    clock_gettime(rustix::time::ClockId::Realtime)
+}
@@ -0,0 +1,192 @@
+//! WASI syscalls via `wasi_snapshot_preview1` imports.
+//!
+//! Error code is WASI `errno`, which uses the same values as `libc` on WASI.
+
+///File descriptor.
+pub type RawFd = libc::c_int;
+
+///Standard input.
+pub const STDIN: RawFd = 0;
+///Standard output.
+pub const STDOUT: RawFd = 1;
+///Standard error.
+pub const STDERR: RawFd = 2;
+
+///Maximum size of atomic write into pipe, as minimum required by POSIX.
+pub const PIPE_BUF: usize = 512;
+
+#[repr(C)]
+struct Ciovec {
+    buf: *const u8,
+    buf_len: usize,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct Fdstat {
+    filetype: u8,
+    flags: u16,
+    rights_base: u64,
+    rights_inheriting: u64,
+}
+
+#[link(wasm_import_module = "wasi_snapshot_preview1")]
+extern "C" {
+    #[link_name = "fd_write"]
+    fn wasi_fd_write(fd: RawFd, iovs: *const Ciovec, iovs_len: usize, written: *mut usize) -> u16;
+    #[link_name = "fd_pwrite"]
+    fn wasi_fd_pwrite(fd: RawFd, iovs: *const Ciovec, iovs_len: usize, offset: u64, written: *mut usize) -> u16;
+    #[link_name = "fd_sync"]
+    fn wasi_fd_sync(fd: RawFd) -> u16;
+    #[link_name = "fd_datasync"]
+    fn wasi_fd_datasync(fd: RawFd) -> u16;
+    #[link_name = "fd_close"]
+    fn wasi_fd_close(fd: RawFd) -> u16;
+    #[link_name = "fd_fdstat_get"]
+    fn wasi_fd_fdstat_get(fd: RawFd, stat: *mut Fdstat) -> u16;
+    #[link_name = "fd_seek"]
+    fn wasi_fd_seek(fd: RawFd, offset: i64, whence: u8, new_offset: *mut u64) -> u16;
+    #[link_name = "clock_time_get"]
+    fn wasi_clock_time_get(clock: u32, precision: u64, time: *mut u64) -> u16;
+}
+
+fn fd_write(fd: RawFd, iovs: &[Ciovec]) -> Result<usize, libc::c_int> {
+    loop {
+        let mut written = 0;
+
+        match unsafe { wasi_fd_write(fd, iovs.as_ptr(), iovs.len(), &mut written) } {
+            0 => break Ok(written),
+            errno => match errno as libc::c_int {
+                //`EINTR` is retried, as signal interrupted call before anything is written.
+                libc::EINTR => continue,
+                errno => break Err(errno),
+            },
+        }
+    }
+}
+
+///Performs single `fd_write`, returning number of bytes written or `errno`.
+pub fn write(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    fd_write(fd, &[Ciovec {
+        buf: data.as_ptr(),
+        buf_len: data.len(),
+    }])
+}
+
+///Performs single write, as there is no `SIGPIPE`.
+pub fn send_nosignal(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    write(fd, data)
+}
+
+///Performs single `fd_write` with multiple buffers, returning number of bytes written or `errno`.
+///
+///Number of buffers is limited to `IOV_LIMIT`.
+pub fn writev(fd: RawFd, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+    let len = core::cmp::min(bufs.len(), super::IOV_LIMIT);
+    let bufs = core::array::from_fn::<_, { super::IOV_LIMIT }, _>(|idx| match bufs.get(idx) {
+        Some(buf) => Ciovec {
+            buf: buf.as_ptr(),
+            buf_len: buf.len(),
+        },
+        None => Ciovec {
+            buf: core::ptr::null(),
+            buf_len: 0,
+        },
+    });
+
+    fd_write(fd, &bufs[..len])
+}
+
+///Performs single `fd_pwrite` at `offset`, returning number of bytes written or `errno`.
+pub fn pwrite(fd: RawFd, data: &[u8], offset: u64) -> Result<usize, libc::c_int> {
+    let iovs = [Ciovec {
+        buf: data.as_ptr(),
+        buf_len: data.len(),
+    }];
+
+    loop {
+        let mut written = 0;
+
+        match unsafe { wasi_fd_pwrite(fd, iovs.as_ptr(), iovs.len(), offset, &mut written) } {
+            0 => break Ok(written),
+            errno => match errno as libc::c_int {
+                libc::EINTR => continue,
+                errno => break Err(errno),
+            },
+        }
+    }
+}
+
+///Synchronizes file content and metadata, returning `errno` on error.
+pub fn fsync(fd: RawFd) -> Result<(), libc::c_int> {
+    match unsafe { wasi_fd_sync(fd) } {
+        0 => Ok(()),
+        errno => Err(errno as libc::c_int),
+    }
+}
+
+///Synchronizes file content, returning `errno` on error.
+pub fn fdatasync(fd: RawFd) -> Result<(), libc::c_int> {
+    match unsafe { wasi_fd_datasync(fd) } {
+        0 => Ok(()),
+        errno => Err(errno as libc::c_int),
+    }
+}
+
+///Closes file descriptor, returning `errno` on error.
+pub fn close(fd: RawFd) -> Result<(), libc::c_int> {
+    match unsafe { wasi_fd_close(fd) } {
+        0 => Ok(()),
+        errno => Err(errno as libc::c_int),
+    }
+}
+
+///Returns whether file descriptor refers to character device, as WASI has no notion of terminal.
+pub fn isatty(fd: RawFd) -> bool {
+    //`filetype::character_device`
+    const CHARACTER_DEVICE: u8 = 2;
+
+    let mut stat = Fdstat {
+        filetype: 0,
+        flags: 0,
+        rights_base: 0,
+        rights_inheriting: 0,
+    };
+    unsafe {
+        wasi_fd_fdstat_get(fd, &mut stat) == 0 && stat.filetype == CHARACTER_DEVICE
+    }
+}
+
+#[cfg(feature = "std")]
+///Performs `fd_seek`, returning new offset or `errno`.
+pub fn seek(fd: RawFd, pos: std::io::SeekFrom) -> Result<u64, libc::c_int> {
+    let (offset, whence) = match pos {
+        std::io::SeekFrom::Start(offset) => (offset as i64, 0),
+        std::io::SeekFrom::Current(offset) => (offset, 1),
+        std::io::SeekFrom::End(offset) => (offset, 2),
+    };
+    let mut new_offset = 0;
+
+    match unsafe { wasi_fd_seek(fd, offset, whence, &mut new_offset) } {
+        0 => Ok(new_offset),
+        errno => Err(errno as libc::c_int),
+    }
+}
+
+fn clock_time_get(clock: u32) -> core::time::Duration {
+    let mut time = 0;
+    unsafe {
+        wasi_clock_time_get(clock, 1, &mut time);
+    }
+    core::time::Duration::from_nanos(time)
+}
+
+///Returns time of monotonic clock.
+pub fn monotonic() -> core::time::Duration {
+    clock_time_get(1)
+}
+
+///Returns time of wall clock since UNIX epoch.
+pub fn realtime() -> core::time::Duration {
+    clock_time_get(0)
+}
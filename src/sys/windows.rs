@@ -0,0 +1,179 @@
+//! Windows syscalls.
+//!
+//! File descriptor is represented by `HANDLE`, while error code is result of `GetLastError`.
+
+use core::{cmp, ffi, ptr};
+
+///File handle.
+pub type RawFd = *mut ffi::c_void;
+
+const STD_INPUT_HANDLE: u32 = -10i32 as u32;
+const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+
+//Standard handles cannot be retrieved in const context, so their identifiers are used instead and resolved on write.
+///Standard input.
+pub const STDIN: RawFd = STD_INPUT_HANDLE as i32 as isize as RawFd;
+///Standard output.
+pub const STDOUT: RawFd = STD_OUTPUT_HANDLE as i32 as isize as RawFd;
+///Standard error.
+pub const STDERR: RawFd = STD_ERROR_HANDLE as i32 as isize as RawFd;
+
+#[repr(C)]
+struct Overlapped {
+    internal: usize,
+    internal_high: usize,
+    offset: u32,
+    offset_high: u32,
+    event: RawFd,
+}
+
+///Maximum size of atomic write into pipe, as minimum required by POSIX.
+pub const PIPE_BUF: usize = 512;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn WriteFile(file: RawFd, buffer: *const u8, len: u32, written: *mut u32, overlapped: *mut Overlapped) -> i32;
+    fn CloseHandle(handle: RawFd) -> i32;
+    fn GetStdHandle(id: u32) -> RawFd;
+    fn FlushFileBuffers(file: RawFd) -> i32;
+    fn GetConsoleMode(handle: RawFd, mode: *mut u32) -> i32;
+    fn SetFilePointerEx(file: RawFd, offset: i64, new_offset: *mut i64, method: u32) -> i32;
+    fn GetLastError() -> u32;
+    fn QueryPerformanceCounter(counter: *mut i64) -> i32;
+    fn QueryPerformanceFrequency(frequency: *mut i64) -> i32;
+    fn GetSystemTimePreciseAsFileTime(time: *mut [u32; 2]);
+}
+
+#[inline]
+pub fn errno() -> libc::c_int {
+    unsafe {
+        GetLastError() as libc::c_int
+    }
+}
+
+#[inline]
+///Resolves standard handle identifier into actual handle.
+pub fn resolve(fd: RawFd) -> RawFd {
+    if fd == STDIN || fd == STDOUT || fd == STDERR {
+        unsafe {
+            GetStdHandle(fd as isize as u32)
+        }
+    } else {
+        fd
+    }
+}
+
+///Performs single `WriteFile`, returning number of bytes written or error code.
+pub fn write(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    let len = cmp::min(data.len(), u32::MAX as usize) as u32;
+    let mut written = 0;
+
+    match unsafe { WriteFile(resolve(fd), data.as_ptr(), len, &mut written, ptr::null_mut()) } {
+        0 => Err(errno()),
+        _ => Ok(written as usize),
+    }
+}
+
+///Performs single `WriteFile` at `offset`, returning number of bytes written or error code.
+///
+///Unlike `pwrite`, file pointer is updated for synchronous handles.
+pub fn pwrite(fd: RawFd, data: &[u8], offset: u64) -> Result<usize, libc::c_int> {
+    let len = cmp::min(data.len(), u32::MAX as usize) as u32;
+    let mut written = 0;
+    let mut overlapped = Overlapped {
+        internal: 0,
+        internal_high: 0,
+        offset: offset as u32,
+        offset_high: (offset >> 32) as u32,
+        event: ptr::null_mut(),
+    };
+
+    match unsafe { WriteFile(resolve(fd), data.as_ptr(), len, &mut written, &mut overlapped) } {
+        0 => Err(errno()),
+        _ => Ok(written as usize),
+    }
+}
+
+///Performs single write, as there is no `SIGPIPE`.
+pub fn send_nosignal(fd: RawFd, data: &[u8]) -> Result<usize, libc::c_int> {
+    write(fd, data)
+}
+
+///Writes first non-empty buffer, as there is no vectored write for handles.
+pub fn writev(fd: RawFd, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+    match bufs.iter().find(|buf| !buf.is_empty()) {
+        Some(buf) => write(fd, buf),
+        None => Ok(0),
+    }
+}
+
+///Flushes file buffers, returning error code on error.
+pub fn fsync(fd: RawFd) -> Result<(), libc::c_int> {
+    match unsafe { FlushFileBuffers(resolve(fd)) } {
+        0 => Err(errno()),
+        _ => Ok(()),
+    }
+}
+
+///Flushes file buffers, as there is no data only variant for handles.
+pub fn fdatasync(fd: RawFd) -> Result<(), libc::c_int> {
+    fsync(fd)
+}
+
+///Closes handle, returning error code on error.
+pub fn close(fd: RawFd) -> Result<(), libc::c_int> {
+    match unsafe { CloseHandle(resolve(fd)) } {
+        0 => Err(errno()),
+        _ => Ok(()),
+    }
+}
+
+///Returns whether handle refers to console.
+pub fn isatty(fd: RawFd) -> bool {
+    let mut mode = 0;
+    unsafe {
+        GetConsoleMode(resolve(fd), &mut mode) != 0
+    }
+}
+
+#[cfg(feature = "std")]
+///Moves file pointer via `SetFilePointerEx`, returning new offset or error code.
+pub fn seek(fd: RawFd, pos: std::io::SeekFrom) -> Result<u64, libc::c_int> {
+    let (offset, method) = match pos {
+        std::io::SeekFrom::Start(offset) => (offset as i64, 0),
+        std::io::SeekFrom::Current(offset) => (offset, 1),
+        std::io::SeekFrom::End(offset) => (offset, 2),
+    };
+    let mut new_offset = 0;
+
+    match unsafe { SetFilePointerEx(resolve(fd), offset, &mut new_offset, method) } {
+        0 => Err(errno()),
+        _ => Ok(new_offset as u64),
+    }
+}
+
+///Returns time of monotonic clock, which is performance counter.
+pub fn monotonic() -> core::time::Duration {
+    let mut counter = 0;
+    let mut frequency = 0;
+    unsafe {
+        QueryPerformanceCounter(&mut counter);
+        QueryPerformanceFrequency(&mut frequency);
+    }
+    let (counter, frequency) = (counter as u64, cmp::max(frequency, 1) as u64);
+    core::time::Duration::new(counter / frequency, ((counter % frequency) * 1_000_000_000 / frequency) as u32)
+}
+
+///Returns time of wall clock since UNIX epoch.
+pub fn realtime() -> core::time::Duration {
+    //`FILETIME` counts 100ns intervals since 1601.
+    const UNIX_EPOCH: u64 = 116_444_736_000_000_000;
+
+    let mut time = [0u32; 2];
+    unsafe {
+        GetSystemTimePreciseAsFileTime(&mut time);
+    }
+    let time = (((time[1] as u64) << 32) | time[0] as u64).saturating_sub(UNIX_EPOCH);
+    core::time::Duration::new(time / 10_000_000, (time % 10_000_000) as u32 * 100)
+}
@@ -0,0 +1,69 @@
+use core::fmt;
+
+///Writer that forwards every write into both `A` and `B`.
+///
+///Write into `B` is performed even if write into `A` fails, in which case first error is returned.
+pub struct TeeWriter<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> TeeWriter<A, B> {
+    #[inline(always)]
+    ///Creates new instance which writes into `a` and `b`
+    pub const fn new(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns references to underlying writers.
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.a, &self.b)
+    }
+
+    #[inline(always)]
+    ///Returns mutable references to underlying writers.
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.a, &mut self.b)
+    }
+
+    #[inline(always)]
+    ///Returns underlying writers.
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug> fmt::Debug for TeeWriter<A, B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("TeeWriter").field("a", &self.a).field("b", &self.b).finish()
+    }
+}
+
+impl<A: fmt::Write, B: fmt::Write> fmt::Write for TeeWriter<A, B> {
+    #[inline]
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let a = self.a.write_str(text);
+        let b = self.b.write_str(text);
+        a.and(b)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: std::io::Write, B: std::io::Write> std::io::Write for TeeWriter<A, B> {
+    ///Writes whole `buf` into both writers, as they may accept different amount of data.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let a = self.a.write_all(buf);
+        let b = self.b.write_all(buf);
+        a.and(b).map(|_| buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let a = self.a.flush();
+        let b = self.b.flush();
+        a.and(b)
+    }
+}
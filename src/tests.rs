@@ -0,0 +1,2207 @@
+use core::fmt::Write;
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use crate::{BufferMode, FdWriter, FdWriterRef, FdWriterSync, LinePrefix, OwnedFdWriter, NewlinePolicy, PrefixedWriter, RawSink};
+
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    //Accepts at most this number of bytes
+    Accept(usize),
+    //Fails with errno
+    Fail(libc::c_int),
+}
+
+//Sink, which follows script for every write call and accepts everything once script is over.
+#[derive(Default)]
+struct ScriptedSink {
+    script: VecDeque<Step>,
+    //Fallback once script is over, accepting everything if `None`
+    then: Option<Step>,
+    data: Vec<u8>,
+    //Data of every successful write call
+    writes: Vec<Vec<u8>>,
+}
+
+impl ScriptedSink {
+    fn new(script: &[Step]) -> Self {
+        Self {
+            script: script.iter().copied().collect(),
+            ..Self::default()
+        }
+    }
+
+    fn then(mut self, step: Step) -> Self {
+        self.then = Some(step);
+        self
+    }
+}
+
+impl RawSink for ScriptedSink {
+    fn raw_write(&mut self, data: &[u8]) -> Result<usize, libc::c_int> {
+        self.raw_write_vectored(&[data])
+    }
+
+    fn raw_write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, libc::c_int> {
+        let total = bufs.iter().map(|buf| buf.len()).sum::<usize>();
+        let mut limit = match self.script.pop_front().or(self.then) {
+            Some(Step::Accept(limit)) => core::cmp::min(limit, total),
+            Some(Step::Fail(errno)) => return Err(errno),
+            None => total,
+        };
+
+        let mut write = Vec::new();
+        for buf in bufs {
+            let size = core::cmp::min(limit, buf.len());
+            write.extend_from_slice(&buf[..size]);
+            limit -= size;
+        }
+        self.data.extend_from_slice(&write);
+        let size = write.len();
+        self.writes.push(write);
+        Ok(size)
+    }
+}
+
+#[cfg(unix)]
+fn pipe() -> (libc::c_int, libc::c_int) {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    (fds[0], fds[1])
+}
+
+#[cfg(unix)]
+fn set_nonblocking(fd: libc::c_int) {
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        assert_eq!(libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK), 0);
+    }
+}
+
+#[cfg(unix)]
+//Reads until end of file or, for non-blocking `fd`, until there is nothing to read.
+fn read_all(fd: libc::c_int) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        match unsafe { libc::read(fd, buffer.as_mut_ptr() as _, buffer.len()) } {
+            size if size > 0 => result.extend_from_slice(&buffer[..size as usize]),
+            _ => break result,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn should_keep_unwritten_data_on_full_pipe() {
+    let (read, write) = pipe();
+    unsafe {
+        assert!(libc::fcntl(write, libc::F_SETPIPE_SZ, 4096) >= 4096);
+    }
+    set_nonblocking(write);
+    set_nonblocking(read);
+
+    //Leave little room in pipe, so that flush stops early.
+    let filler = [b'-'; 4000];
+    assert_eq!(unsafe { libc::write(write, filler.as_ptr() as _, filler.len()) }, filler.len() as isize);
+
+    let data = (0..4096u32).map(|idx| b'a' + (idx % 26) as u8).collect::<Vec<_>>();
+    let mut writer = FdWriter::new(write);
+    //Data as large as buffer is written directly, so fill it in two steps
+    let (first, second) = data.split_at(data.len() / 2);
+    assert_eq!(writer.try_write_data(first), Ok(first.len()));
+    assert_eq!(writer.try_write_data(second), Ok(second.len()));
+    assert_eq!(writer.as_slice(), data);
+
+    let error = writer.try_flush().expect_err("To fail on full pipe");
+    assert_eq!(error.errno(), libc::EAGAIN);
+    assert_eq!(writer.last_error(), Some(error));
+    //Unwritten data is kept at the front of buffer
+    assert_eq!(writer.as_slice(), &data[error.written()..]);
+
+    let mut output = read_all(read);
+    writer.try_flush().expect("To flush once pipe is drained");
+    assert!(writer.as_slice().is_empty());
+    output.extend_from_slice(&read_all(read));
+
+    assert_eq!(output[..filler.len()], filler);
+    assert_eq!(output[filler.len()..], data);
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_everything_into_slow_socket() {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) }, 0);
+    let (read, write) = (fds[0], fds[1]);
+
+    let reader = std::thread::spawn(move || {
+        let mut result = Vec::new();
+        let mut buffer = [0u8; 512];
+        loop {
+            std::thread::sleep(std::time::Duration::from_micros(50));
+            match unsafe { libc::read(read, buffer.as_mut_ptr() as _, buffer.len()) } {
+                size if size > 0 => result.extend_from_slice(&buffer[..size as usize]),
+                _ => break result,
+            }
+        }
+    });
+
+    let data = (0..256 * 1024u32).map(|idx| match idx % 100 {
+        99 => b'\n',
+        idx => b'a' + (idx % 26) as u8,
+    }).collect::<Vec<_>>();
+    let mut writer = FdWriter::new(write);
+    for chunk in data.chunks(1000) {
+        assert_eq!(writer.try_write_data(chunk), Ok(chunk.len()));
+    }
+    writer.try_flush().expect("To flush");
+    assert_eq!(writer.last_error(), None);
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    assert_eq!(reader.join().expect("To read"), data);
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_ignore_empty_writes() {
+    let (read, write) = pipe();
+
+    let mut writer = FdWriter::new(write);
+    writer.write_data(&[]);
+    assert_eq!(writer.try_write_data(&[]), Ok(0));
+    assert!(writer.as_slice().is_empty());
+
+    //Exactly full buffer
+    let data = [b'a'; 4096];
+    writer.write_data(&data[..4000]);
+    writer.write_data(&data[4000..]);
+    assert_eq!(writer.as_slice().len(), data.len());
+    writer.write_data(&[]);
+    assert_eq!(writer.as_slice(), data);
+
+    //Full buffer is flushed to make room for more data
+    writer.write_data(b"b\n");
+    assert!(writer.as_slice().is_empty());
+
+    //Right after flush
+    writer.write_data(&[]);
+    assert_eq!(writer.try_write_data(&[]), Ok(0));
+    write!(writer, "").expect("To write empty string");
+    assert!(writer.as_slice().is_empty());
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    let output = read_all(read);
+    assert_eq!(output[..data.len()], data);
+    assert_eq!(output[data.len()..], *b"b\n");
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_retry_write_interrupted_by_signal() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::os::unix::thread::JoinHandleExt;
+
+    static SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn on_signal(_: libc::c_int) {
+        SIGNALS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    //Without SA_RESTART blocked write fails with EINTR
+    unsafe {
+        let mut action: libc::sigaction = core::mem::zeroed();
+        action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as usize;
+        libc::sigemptyset(&mut action.sa_mask);
+        assert_eq!(libc::sigaction(libc::SIGUSR1, &action, core::ptr::null_mut()), 0);
+    }
+
+    let (read, write) = pipe();
+    let data = (0..256 * 1024u32).map(|idx| b'a' + (idx % 26) as u8).collect::<Vec<_>>();
+    let input = data.clone();
+    let writer = std::thread::spawn(move || {
+        let mut writer = FdWriter::new(write);
+        let result = writer.try_write_data(&input).and_then(|_| writer.try_flush());
+        drop(writer);
+        unsafe {
+            libc::close(write);
+        }
+        result
+    });
+
+    //Nobody reads pipe yet, so writer is blocked once pipe is full
+    for _ in 0..5 {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(unsafe { libc::pthread_kill(writer.as_pthread_t(), libc::SIGUSR1) }, 0);
+    }
+
+    let output = read_all(read);
+    assert_eq!(writer.join().expect("To write"), Ok(()));
+    assert_eq!(SIGNALS.load(Ordering::SeqCst), 5);
+    assert_eq!(output, data);
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_through_tiny_buffer() {
+    let (read, write) = pipe();
+
+    let mut data = Vec::new();
+    for idx in 0..50 {
+        writeln!(Adapter(&mut data), "{}:{}", idx, "x".repeat(idx % 19)).expect("To format");
+    }
+
+    let mut writer = FdWriter::<8>::new_sized(write);
+    for (idx, chunk) in data.chunks(5).enumerate() {
+        //Uneven chunks to hit every position within buffer
+        let (first, second) = chunk.split_at(idx % chunk.len());
+        writer.write_data(first);
+        writer.write_data(second);
+        assert!(writer.as_slice().len() <= 8);
+    }
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    assert_eq!(read_all(read), data);
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn should_handle_short_writes_on_pipe() {
+    let (read, write) = pipe();
+    unsafe {
+        assert!(libc::fcntl(write, libc::F_SETPIPE_SZ, 4096) >= 4096);
+    }
+    set_nonblocking(write);
+    set_nonblocking(read);
+
+    //Buffer is larger than pipe, hence pipe can only accept part of it.
+    let data = (0..70_000u32).map(|idx| idx as u8).filter(|byte| *byte != b'\n').take(70_000 - 1).collect::<Vec<_>>();
+    let mut writer = FdWriter::<70_000>::new_sized(write);
+    writer.write_data(&data);
+    assert_eq!(writer.as_slice(), data);
+
+    let mut output = Vec::new();
+    let mut short_writes = 0;
+    while let Err(error) = writer.try_flush() {
+        assert_eq!(error.errno(), libc::EAGAIN);
+        if error.written() > 0 {
+            short_writes += 1;
+        }
+        //Unwritten data is kept at the front of buffer
+        assert_eq!(writer.as_slice(), &data[data.len() - writer.as_slice().len()..]);
+        output.extend_from_slice(&read_all(read));
+    }
+    output.extend_from_slice(&read_all(read));
+
+    assert!(short_writes > 0);
+    assert_eq!(output, data);
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+//Collects formatted text into bytes.
+struct Adapter<'a>(&'a mut Vec<u8>);
+
+impl Write for Adapter<'_> {
+    fn write_str(&mut self, text: &str) -> core::fmt::Result {
+        self.0.extend_from_slice(text.as_bytes());
+        Ok(())
+    }
+}
+
+//Xorshift generator, good enough to pick split points.
+struct Random(u32);
+
+impl Random {
+    fn next(&mut self, max: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as usize % max
+    }
+}
+
+#[cfg(unix)]
+fn write_random_splits<const N: usize>(random: &mut Random, data: &[u8]) -> Vec<u8> {
+    let (read, write) = pipe();
+
+    let mut writer = FdWriter::<N>::new_sized(write);
+    let mut remaining = data;
+    while !remaining.is_empty() {
+        //Zero length chunks are included on purpose
+        let (chunk, rest) = remaining.split_at(random.next(remaining.len().min(3 * N)) + random.next(2));
+        remaining = rest;
+
+        assert_eq!(writer.try_write_data(chunk), Ok(chunk.len()));
+        if chunk.last() == Some(&b'\n') {
+            assert!(writer.as_slice().is_empty());
+        }
+        if random.next(8) == 0 {
+            writer.flush();
+            assert!(writer.as_slice().is_empty());
+        }
+    }
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    let output = read_all(read);
+    unsafe {
+        libc::close(read);
+    }
+    output
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_data_split_at_random_points() {
+    let mut random = Random(0x5eed);
+    for _ in 0..20 {
+        //Fits into pipe, so that nothing blocks without reader
+        let data = (0..4096).map(|_| match random.next(10) {
+            0 => b'\n',
+            idx => b'a' + idx as u8,
+        }).collect::<Vec<_>>();
+
+        assert_eq!(write_random_splits::<8>(&mut random, &data), data);
+        assert_eq!(write_random_splits::<64>(&mut random, &data), data);
+        assert_eq!(write_random_splits::<4096>(&mut random, &data), data);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_flush_newline_exactly_filling_buffer() {
+    let (read, write) = pipe();
+
+    let mut writer = FdWriter::<8>::new_sized(write);
+    //Data is exact multiple of capacity
+    writer.write_data(b"0123456789abcde\n");
+    assert!(writer.as_slice().is_empty());
+
+    //Data exactly fills buffer
+    writer.write_data(b"0123456\n");
+    assert!(writer.as_slice().is_empty());
+    writer.write_data(b"x");
+    assert_eq!(writer.as_slice(), b"x");
+
+    //Right after manual flush
+    writer.flush();
+    writer.write_data(b"y\n");
+    assert!(writer.as_slice().is_empty());
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    assert_eq!(read_all(read), b"0123456789abcde\n0123456\nxy\n");
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_discard_data_on_clear() {
+    let (read, write) = pipe();
+
+    let mut writer = FdWriter::new(write);
+    writer.write_data(b"discarded");
+    writer.clear();
+    assert!(writer.as_slice().is_empty());
+
+    writer.write_data(b"also discarded");
+    writer.clear();
+    writer.clear();
+    writer.write_data(b"written\n");
+    //Nothing left to discard after flush
+    writer.clear();
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    assert_eq!(read_all(read), b"written\n");
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_flush_newline_at_capacity_boundary() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    //Newline lands just before, exactly on and just after boundary of 8 byte buffer
+    for size in [6usize, 7, 8, 9, 15, 16, 17] {
+        let mut line = Vec::new();
+        line.resize(size, b'a');
+        line.push(b'\n');
+
+        let mut writer = FdWriter::<8>::new_sized(write);
+        writer.write_data(&line);
+        assert!(writer.as_slice().is_empty(), "line of {} bytes is not flushed", size);
+        assert_eq!(read_all(read), line);
+
+        //Newline split into next write
+        writer.write_data(&line[..size]);
+        writer.write_data(b"\n");
+        assert!(writer.as_slice().is_empty(), "line of {} bytes is not flushed after separate newline", size);
+        assert_eq!(read_all(read), line);
+    }
+
+    //Only complete lines are flushed
+    let mut writer = FdWriter::<8>::new_sized(write);
+    writer.write_data(b"1234567\n89");
+    assert_eq!(writer.as_slice(), b"89");
+    assert_eq!(read_all(read), b"1234567\n");
+    writer.clear();
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(target_os = "linux")]
+//Number of write syscalls made by current thread so far.
+fn write_syscalls() -> usize {
+    let io = std::fs::read_to_string("/proc/thread-self/io").expect("To read io stats");
+    let count = io.lines().find_map(|line| line.strip_prefix("syscw: ")).expect("To find syscw");
+    count.parse().expect("To parse syscw")
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn should_write_large_data_directly() {
+    let fd = unsafe {
+        libc::open("/dev/null\0".as_ptr() as *const _, libc::O_WRONLY)
+    };
+    assert!(fd >= 0);
+
+    let data = std::vec![b'a'; 1024 * 1024];
+    let mut writer = FdWriter::new(fd);
+    writer.write_data(b"buffered");
+
+    let before = write_syscalls();
+    assert_eq!(writer.try_write_data(&data), Ok(data.len()));
+    //Buffered data and direct write go into single writev, instead of write per buffer
+    assert_eq!(write_syscalls() - before, 1);
+    assert!(writer.as_slice().is_empty());
+
+    let before = write_syscalls();
+    assert_eq!(writer.try_write_data(&data[..8192]), Ok(8192));
+    assert_eq!(write_syscalls() - before, 1);
+
+    drop(writer);
+    unsafe {
+        libc::close(fd);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn should_report_partial_direct_write() {
+    let (read, write) = pipe();
+    unsafe {
+        assert!(libc::fcntl(write, libc::F_SETPIPE_SZ, 4096) >= 4096);
+    }
+    set_nonblocking(write);
+    set_nonblocking(read);
+
+    let data = (0..16384u32).map(|idx| b'a' + (idx % 26) as u8).collect::<Vec<_>>();
+    let mut writer = FdWriter::new(write);
+    let written = writer.try_write_data(&data).expect("To write part of data");
+    assert!(written > 0 && written < data.len(), "written={}", written);
+    assert!(writer.last_error().expect("To have error").is_would_block());
+    //Nothing of direct write is buffered, so caller retries the rest
+    assert!(writer.as_slice().is_empty());
+    assert_eq!(read_all(read), &data[..written]);
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_close_pipe_after_drop_of_owned_writer() {
+    let (read, write) = pipe();
+
+    let mut writer = unsafe {
+        OwnedFdWriter::new(write)
+    };
+    writer.write_data(b"owned");
+    drop(writer);
+
+    //End of file is reached only once write end is closed
+    assert_eq!(read_all(read), b"owned");
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn should_close_owned_fd_after_drop() {
+    use std::os::fd::FromRawFd;
+
+    let (read, write) = pipe();
+
+    let mut writer = OwnedFdWriter::<16>::from(unsafe { std::os::fd::OwnedFd::from_raw_fd(write) });
+    writer.write_data(b"from OwnedFd");
+    drop(writer);
+
+    assert_eq!(read_all(read), b"from OwnedFd");
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_not_close_borrowed_fd_after_drop() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = FdWriter::new(write);
+    writer.write_data(b"first");
+    drop(writer);
+
+    let mut writer = FdWriter::new(write);
+    writer.write_data(b" second");
+    drop(writer);
+
+    assert_eq!(read_all(read), b"first second");
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn should_round_trip_raw_fd() {
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    let (read, write) = pipe();
+
+    let mut writer = unsafe {
+        FdWriter::<16>::from_raw_fd(write)
+    };
+    assert_eq!(writer.as_raw_fd(), write);
+    writer.write_data(b"raw");
+    drop(writer);
+
+    let writer = unsafe {
+        OwnedFdWriter::<16>::from_raw_fd(write)
+    };
+    assert_eq!(writer.as_raw_fd(), write);
+    drop(writer);
+
+    assert_eq!(read_all(read), b"raw");
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn should_write_vectored() {
+    use std::io::{IoSlice, Write};
+
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = FdWriter::<8>::new_sized(write);
+    //Empty slice in the middle and total exactly of buffer capacity
+    let bufs = [IoSlice::new(b"abc"), IoSlice::new(b""), IoSlice::new(b"defgh")];
+    assert_eq!(writer.write_vectored(&bufs).expect("To write"), 8);
+    assert_eq!(writer.as_slice(), b"abcdefgh");
+    assert!(read_all(read).is_empty());
+
+    //Does not fit, so buffer is flushed and slices are written directly
+    let bufs = [IoSlice::new(b"ij"), IoSlice::new(b""), IoSlice::new(b"klmnopqr\n")];
+    assert_eq!(writer.write_vectored(&bufs).expect("To write"), 11);
+    assert!(writer.as_slice().is_empty());
+    assert_eq!(read_all(read), b"abcdefghijklmnopqr\n");
+
+    //Only empty slices
+    let bufs = [IoSlice::new(b""), IoSlice::new(b"")];
+    assert_eq!(writer.write_vectored(&bufs).expect("To write"), 0);
+    assert!(writer.as_slice().is_empty());
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_not_flush_line_without_auto_flush() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = FdWriter::new(write);
+    writer.set_auto_flush(false);
+    writer.write_data(b"a\n");
+    assert_eq!(writer.as_slice(), b"a\n");
+    assert!(read_all(read).is_empty());
+
+    writer.set_auto_flush(true);
+    writer.write_data(b"b\n");
+    assert!(writer.as_slice().is_empty());
+    assert_eq!(read_all(read), b"a\nb\n");
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_format_debug() {
+    let mut writer = FdWriter::<16>::new_sized(1);
+    writer.write_data(b"secret");
+    assert_eq!(std::format!("{:?}", writer), "FdWriter { sink: 1, buffered: 6, capacity: 16, preview: \"secret\" }");
+    writer.clear();
+
+    let mut writer = FdWriter::<64>::new_sized(1);
+    writer.write_data(b"\"tab\"\t\xff");
+    writer.write_data(&[b'a'; 32]);
+    assert_eq!(std::format!("{:?}", writer), "FdWriter { sink: 1, buffered: 39, capacity: 64, preview: \"\\\"tab\\\"\\t\\xffaaaaaaaaaaaaaaaaaaaaaaaaa\"... }");
+    writer.clear();
+
+    let mut writer = unsafe {
+        OwnedFdWriter::<16>::new_sized(libc::dup(1))
+    };
+    let fd = writer.fd();
+    writer.write_data(b"secret");
+    assert_eq!(std::format!("{:?}", writer), std::format!("OwnedFdWriter {{ fd: {}, buffered: 6 }}", fd));
+    writer.clear();
+}
+
+#[cfg(target_os = "linux")]
+//Socket pair preserving boundaries of every write, with non-blocking read end.
+fn packet_pair() -> (libc::c_int, libc::c_int) {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_SEQPACKET, 0, fds.as_mut_ptr()) }, 0);
+    set_nonblocking(fds[0]);
+    (fds[0], fds[1])
+}
+
+#[cfg(target_os = "linux")]
+//Reads every write made into packet socket so far.
+fn read_packets(fd: libc::c_int) -> Vec<Vec<u8>> {
+    let mut result = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        match unsafe { libc::read(fd, buffer.as_mut_ptr() as _, buffer.len()) } {
+            size if size > 0 => result.push(buffer[..size as usize].to_vec()),
+            _ => break result,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn should_split_long_lines_when_ensuring_newline() {
+    let (read, write) = packet_pair();
+
+    let mut writer = FdWriter::<8>::new_sized(write);
+    writer.set_ensure_newline(Some(NewlinePolicy::Hold));
+    writer.write_data(b"short\n0123456789abcdefghij\nend");
+    writer.flush();
+
+    let packets = read_packets(read);
+    assert!(packets.iter().all(|packet| packet.last() == Some(&b'\n')), "{:?}", packets);
+    let text = packets.concat().into_iter().filter(|byte| *byte != b'\n').collect::<Vec<_>>();
+    assert_eq!(text, b"short0123456789abcdefghijend");
+
+    writer.set_ensure_newline(Some(NewlinePolicy::Append));
+    writer.write_data(b"0123456789\n");
+    let packets = read_packets(read);
+    assert_eq!(packets, [&b"0123456\n"[..], b"789\n"]);
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[test]
+fn should_return_partial_count_from_buffered_write_vectored() {
+    use std::io::{IoSlice, Write};
+
+    let (read, write) = pipe();
+    unsafe {
+        assert!(libc::fcntl(write, libc::F_SETPIPE_SZ, 4096) >= 4096);
+    }
+    set_nonblocking(write);
+    set_nonblocking(read);
+
+    //Leave room for single line only
+    let filler = [b'-'; 4096 - 10];
+    assert_eq!(unsafe { libc::write(write, filler.as_ptr() as _, filler.len()) }, filler.len() as isize);
+
+    let mut writer = FdWriter::<8>::new_sized(write);
+    writer.set_ensure_newline(Some(NewlinePolicy::Hold));
+
+    let data = b"abcdef\nghijkl\nmnopqr\nstuv\n";
+    let bufs = [IoSlice::new(&data[..10]), IoSlice::new(&data[10..20]), IoSlice::new(&data[20..])];
+
+    let accepted = writer.write_vectored(&bufs).expect("To accept part of data");
+    assert!(accepted > 0 && accepted < data.len(), "accepted={}", accepted);
+
+    let mut output = read_all(read);
+    assert_eq!(output[..filler.len()], filler);
+    output.drain(..filler.len());
+    output.extend_from_slice(writer.as_slice());
+    assert_eq!(output, &data[..accepted]);
+
+    writer.clear();
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn should_forward_write_vectored_of_fd_wrappers() {
+    use std::io::{IoSlice, Write};
+    use std::os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd};
+
+    let (read, write) = pipe();
+    set_nonblocking(read);
+    let write = unsafe {
+        OwnedFd::from_raw_fd(write)
+    };
+
+    let mut writer = FdWriter::from_borrowed(write.as_fd());
+    assert_eq!(writer.as_fd().as_raw_fd(), write.as_raw_fd());
+    let bufs = [IoSlice::new(b"borrowed "), IoSlice::new(b"")];
+    assert_eq!(writer.write_vectored(&bufs).expect("To write"), 9);
+    writer.flush().expect("To flush");
+    drop(writer);
+    assert_eq!(read_all(read), b"borrowed ");
+
+    let mut writer = FdWriter::from_owned(write);
+    let bufs = [IoSlice::new(b"owned"), IoSlice::new(b"\n")];
+    assert_eq!(writer.write_vectored(&bufs).expect("To write"), 6);
+    writer.close().expect("To flush and close");
+    assert_eq!(read_all(read), b"owned\n");
+
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(all(feature = "async-tokio", unix))]
+#[test]
+fn should_fail_async_write_with_would_block_on_full_pipe() {
+    use core::pin::Pin;
+    use core::task::{Context, Poll, Waker};
+    use ::tokio::io::AsyncWrite;
+
+    let (read, write) = pipe();
+    set_nonblocking(write);
+    set_nonblocking(read);
+
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut writer = FdWriter::<64>::new_sized(write);
+    writer.set_mode(crate::BufferMode::Full);
+    let chunk = [b'a'; 16];
+
+    let mut accepted = 0;
+    let error = loop {
+        match Pin::new(&mut writer).poll_write(&mut cx, &chunk) {
+            Poll::Ready(Ok(size)) => accepted += size,
+            Poll::Ready(Err(error)) => break error,
+            Poll::Pending => panic!("Writer is never pending"),
+        }
+    };
+    assert_eq!(error.kind(), std::io::ErrorKind::WouldBlock);
+    match Pin::new(&mut writer).poll_flush(&mut cx) {
+        Poll::Ready(Err(error)) => assert_eq!(error.kind(), std::io::ErrorKind::WouldBlock),
+        _ => panic!("Flush should fail on full pipe"),
+    }
+
+    let mut output = read_all(read);
+    assert!(matches!(Pin::new(&mut writer).poll_shutdown(&mut cx), Poll::Ready(Ok(()))));
+    assert!(writer.as_slice().is_empty());
+    output.extend_from_slice(&read_all(read));
+    assert_eq!(output.len(), accepted);
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_large_data_with_buffered_prefix() {
+    let (read, write) = pipe();
+    let reader = std::thread::spawn(move || read_all(read));
+
+    let data = (0..64 * 1024u32).map(|idx| b'a' + (idx % 26) as u8).collect::<Vec<_>>();
+    let mut writer = FdWriter::new(write);
+    //Buffered bytes go together with large write
+    writer.write_data(&data[..100]);
+    assert_eq!(writer.as_slice(), &data[..100]);
+    assert_eq!(writer.try_write_data(&data[100..]), Ok(data.len() - 100));
+    assert!(writer.as_slice().is_empty());
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    assert_eq!(reader.join().expect("To read"), data);
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn should_join_many_items_without_syscall_per_item() {
+    let (read, write) = pipe();
+    let reader = std::thread::spawn(move || read_all(read));
+
+    let mut writer = FdWriter::new(write);
+    let before = write_syscalls();
+    writer.write_join(0..10_000, ",");
+    writer.write_join_bytes((0..10_000).map(|_| b"ab"), b";");
+    writer.flush();
+    //Data is written once buffer is full
+    let syscalls = write_syscalls() - before;
+    assert!(syscalls < 40, "{} syscalls", syscalls);
+
+    //Empty iterators write nothing
+    writer.write_join(core::iter::empty::<u32>(), ",");
+    writer.write_join_bytes(core::iter::empty::<&[u8]>(), b",");
+    assert!(writer.as_slice().is_empty());
+
+    //Items longer than buffer
+    let long = std::vec![b'x'; 5000];
+    writer.write_join_bytes([&long[..], b"", &long[..]], b"|");
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    let output = reader.join().expect("To read");
+    let mut expected = (0..10_000).map(|idx| std::format!("{}", idx)).collect::<Vec<_>>().join(",").into_bytes();
+    expected.extend_from_slice(&[&b"ab"[..]; 10_000].join(&b';'));
+    expected.extend_from_slice(&[&long[..], b"", &long[..]].join(&b'|'));
+    assert_eq!(output, expected);
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_report_closed_pipe() {
+    let (read, write) = pipe();
+    unsafe {
+        libc::close(read);
+    }
+
+    let mut writer = FdWriter::<16>::new_sized(write);
+    assert!(writeln!(writer, "line {}", 1).is_err());
+    let error = writer.take_last_error().expect("To have error");
+    assert_eq!(error.errno(), libc::EPIPE);
+    assert_eq!(writer.last_error(), None);
+    //Unwritten data stays intact and following writes append to it
+    assert_eq!(writer.as_slice(), b"line 1\n");
+    assert!(write!(writer, "x").is_ok());
+    assert_eq!(writer.as_slice(), b"line 1\nx");
+    assert!(writeln!(writer, "y").is_err());
+    assert_eq!(writer.as_slice(), b"line 1\nxy\n");
+
+    writer.clear();
+    drop(writer);
+    unsafe {
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+#[test]
+fn should_wait_for_pipe_to_become_writable() {
+    use ::tokio::io::AsyncWriteExt;
+
+    let (read, write) = pipe();
+    let reader = std::thread::spawn(move || read_all(read));
+
+    let runtime = ::tokio::runtime::Builder::new_current_thread().enable_io().build().expect("To create runtime");
+    let data = (0..1024 * 1024u32).map(|idx| b'a' + (idx % 26) as u8).collect::<Vec<_>>();
+    runtime.block_on(async {
+        let fd = unsafe { std::os::fd::FromRawFd::from_raw_fd(write) };
+        let mut writer = crate::AsyncFdWriter::<64>::from_owned(fd).expect("To register fd");
+        writer.write_all(&data).await.expect("To write data");
+        writer.shutdown().await.expect("To shutdown");
+        assert!(writer.get_ref().is_none());
+    });
+
+    assert_eq!(reader.join().expect("To read"), data);
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+#[test]
+fn should_restore_flags_of_borrowed_fd() {
+    use ::tokio::io::AsyncWriteExt;
+
+    fn is_nonblocking(fd: libc::c_int) -> bool {
+        unsafe { libc::fcntl(fd, libc::F_GETFL) & libc::O_NONBLOCK != 0 }
+    }
+
+    let (read, write) = pipe();
+    set_nonblocking(read);
+    assert!(!is_nonblocking(write));
+
+    let runtime = ::tokio::runtime::Builder::new_current_thread().enable_io().build().expect("To create runtime");
+    runtime.block_on(async {
+        let mut writer = crate::AsyncFdWriter::<64>::new(write).expect("To register fd");
+        assert!(is_nonblocking(write));
+        writer.write_all(b"shutdown").await.expect("To write data");
+        writer.shutdown().await.expect("To shutdown");
+        assert!(!is_nonblocking(write));
+
+        let mut writer = crate::AsyncFdWriter::<64>::new(write).expect("To register fd");
+        writer.write_all(b" drop").await.expect("To write data");
+        drop(writer);
+        assert!(!is_nonblocking(write));
+    });
+
+    assert_eq!(read_all(read), b"shutdown drop");
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "ufmt", unix))]
+#[test]
+fn should_format_with_ufmt_into_pipe() {
+    use ::ufmt::{uwrite, uwriteln};
+
+    let (read, write) = pipe();
+
+    let mut writer = FdWriter::<16>::new_sized(write);
+    uwrite!(writer, "int={} neg={}", 42u32, -7i64).expect("To write");
+    uwriteln!(writer, " str={}", "text").expect("To write line");
+    //Line mode flushes complete line
+    assert!(writer.is_empty());
+    uwrite!(writer, "tail").expect("To write tail");
+    assert_eq!(writer.as_slice(), b"tail");
+    drop(writer);
+
+    unsafe {
+        libc::close(write);
+    }
+    assert_eq!(read_all(read), b"int=42 neg=-7 str=text\ntail");
+    unsafe {
+        libc::close(read);
+    }
+}
+
+#[cfg(all(feature = "ufmt", unix))]
+#[test]
+fn should_return_write_error_from_ufmt() {
+    let mut writer = FdWriter::<4>::new_sized(-1);
+    let error = ::ufmt::uwrite!(writer, "{}", 123456u32).expect_err("To fail");
+    assert_eq!(error.errno(), libc::EBADF);
+    writer.clear();
+}
+
+#[cfg(unix)]
+#[test]
+fn should_not_interleave_lines_of_threads() {
+    const THREADS: usize = 4;
+    const LINES: usize = 200;
+
+    let (read, write) = pipe();
+    let reader = std::thread::spawn(move || read_all(read));
+    let writer = FdWriterSync::<256>::new_sized(write);
+
+    std::thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let mut writer = &writer;
+            scope.spawn(move || {
+                for line in 0..LINES {
+                    writeln!(writer, "thread={} line={} {}", thread, line, "x".repeat(line % 50)).expect("To write line");
+                }
+            });
+        }
+    });
+    drop(writer);
+    unsafe {
+        libc::close(write);
+    }
+
+    let output = std::string::String::from_utf8(reader.join().expect("To read")).expect("Valid UTF-8");
+    unsafe {
+        libc::close(read);
+    }
+
+    let mut next = [0usize; THREADS];
+    for text in output.lines() {
+        let mut parts = text.split(' ');
+        let thread = parts.next().and_then(|part| part.strip_prefix("thread=")).and_then(|part| part.parse::<usize>().ok()).expect("Thread id");
+        let line = parts.next().and_then(|part| part.strip_prefix("line=")).and_then(|part| part.parse::<usize>().ok()).expect("Line number");
+        assert_eq!(line, next[thread], "Line is out of order: {}", text);
+        assert_eq!(parts.next(), Some("x".repeat(line % 50).as_str()), "Line is torn: {}", text);
+        assert_eq!(parts.next(), None);
+        next[thread] += 1;
+    }
+    assert_eq!(next, [LINES; THREADS]);
+}
+
+#[cfg(unix)]
+//Path of temporary file, unique to process and test.
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(std::format!("fd-writer-{}-{}", std::process::id(), name))
+}
+
+#[cfg(unix)]
+//Creates temporary file, opened for reading and writing.
+fn temp_file(name: &str) -> (std::path::PathBuf, libc::c_int) {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = temp_path(name);
+    let mut raw_path = path.as_os_str().as_bytes().to_vec();
+    raw_path.push(0);
+    let fd = unsafe {
+        libc::open(raw_path.as_ptr() as *const _, libc::O_RDWR | libc::O_CREAT | libc::O_TRUNC, 0o600)
+    };
+    assert!(fd >= 0, "Unable to create {}", path.display());
+    (path, fd)
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_records_at_offsets() {
+    let (path, fd) = temp_file("write_data_at");
+
+    let mut writer = FdWriter::new(fd);
+    writer.write_data_at(b"first", 0).expect("To write at 0");
+    writer.write_data_at(b"second", 100).expect("To write at 100");
+    //Position of file descriptor is not affected
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 0);
+
+    //Streaming write continues from current position
+    writer.write_data(b"F");
+    writer.flush();
+    drop(writer);
+    unsafe {
+        libc::close(fd);
+    }
+
+    let content = std::fs::read(&path).expect("To read file");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(content.len(), 106);
+    assert_eq!(content[..5], *b"First");
+    assert!(content[5..100].iter().all(|byte| *byte == 0));
+    assert_eq!(content[100..], *b"second");
+}
+
+//Formats same as `PanicInfo`, which cannot be constructed outside of panic.
+struct PanicLike<'a> {
+    message: Option<&'a str>,
+    location: Option<(&'a str, u32, u32)>,
+}
+
+impl core::fmt::Display for PanicLike<'_> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.write_str("panicked")?;
+        if let Some((file, line, column)) = self.location {
+            write!(fmt, " at {}:{}:{}", file, line, column)?;
+        }
+        if let Some(message) = self.message {
+            write!(fmt, ":\n{}", message)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_panic_info() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    crate::panic::write_truncated(write, &PanicLike {
+        message: Some("oops"),
+        location: Some(("src/main.rs", 10, 5)),
+    });
+    assert_eq!(read_all(read), b"panicked at src/main.rs:10:5:\noops\n");
+
+    crate::panic::write_truncated(write, &PanicLike {
+        message: None,
+        location: None,
+    });
+    assert_eq!(read_all(read), b"panicked\n");
+
+    //Long message is truncated at character boundary
+    let message = "é".repeat(1000);
+    crate::panic::write_truncated(write, &PanicLike {
+        message: Some(&message),
+        location: None,
+    });
+    let output = read_all(read);
+    assert!(output.len() <= 512);
+    assert_eq!(output.last(), Some(&b'\n'));
+    let text = core::str::from_utf8(&output).expect("Valid UTF-8");
+    assert!(text.starts_with("panicked:\néé"));
+
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_write_into_vec_sink() {
+    let mut writer = FdWriter::<8, Vec<u8>>::with_sink_sized(Vec::new());
+
+    writer.write_data(b"1234");
+    assert!(writer.sink().is_empty());
+
+    writer.write_data(b"5678");
+    assert!(writer.sink().is_empty());
+
+    //Overflowing buffer writes buffered data together with new data
+    writer.write_data(b"9");
+    assert_eq!(writer.sink().as_slice(), b"123456789");
+    assert_eq!(writer.len(), 0);
+
+    writer.write_data(b"0");
+    writer.flush();
+    assert_eq!(writer.sink().as_slice(), b"1234567890");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_flush_on_write_byte_of_delimiter() {
+    let mut writer = FdWriter::<8, Vec<u8>>::with_sink_sized(Vec::new());
+    writer.write_byte(b'a');
+    writer.write_byte(b'b');
+    assert!(writer.sink().is_empty());
+    assert_eq!(writer.len(), 2);
+
+    writer.write_byte(b'\n');
+    assert_eq!(writer.sink().as_slice(), b"ab\n");
+    assert_eq!(writer.len(), 0);
+
+    //Without auto flush byte is only buffered
+    writer.set_auto_flush(false);
+    writer.write_byte(b'\n');
+    assert_eq!(writer.sink().as_slice(), b"ab\n");
+    assert_eq!(writer.len(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_track_buffer_counters() {
+    let mut writer = FdWriter::<8, Vec<u8>>::with_sink_sized(Vec::new());
+    assert!(writer.is_empty());
+    assert_eq!(writer.capacity(), 8);
+    assert_eq!(writer.remaining(), 8);
+    assert_eq!(writer.as_str(), Some(""));
+
+    //Partial fill
+    writer.write_data(b"abc");
+    assert!(!writer.is_empty());
+    assert_eq!(writer.len(), 3);
+    assert_eq!(writer.remaining(), 5);
+    assert_eq!(writer.as_str(), Some("abc"));
+
+    //Automatic flush on newline
+    writer.write_data(b"d\nef");
+    assert_eq!(writer.sink().as_slice(), b"abcd\n");
+    assert_eq!(writer.len(), 2);
+    assert_eq!(writer.remaining(), 6);
+    assert_eq!(writer.as_str(), Some("ef"));
+
+    //Large write goes through directly
+    writer.write_data(b"0123456789");
+    assert_eq!(writer.sink().as_slice(), b"abcd\nef0123456789");
+    assert!(writer.is_empty());
+    assert_eq!(writer.remaining(), 8);
+
+    writer.write_data(b"\xff");
+    assert_eq!(writer.len(), 1);
+    assert_eq!(writer.as_str(), None);
+}
+
+#[cfg(feature = "std")]
+//Writer accepting at most `limit` bytes per write.
+struct ShortWriter {
+    data: Vec<u8>,
+    limit: usize,
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for ShortWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let size = core::cmp::min(self.limit, buf.len());
+        self.data.extend_from_slice(&buf[..size]);
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::RawSink for ShortWriter {
+    fn raw_write(&mut self, data: &[u8]) -> Result<usize, libc::c_int> {
+        std::io::Write::write(self, data).map_err(|_| libc::EIO)
+    }
+}
+
+//Prefix with line number, making it possible to see when prefix is taken.
+struct LineNumber {
+    line: usize,
+    text: std::string::String,
+}
+
+impl LinePrefix for LineNumber {
+    fn start_line(&mut self) {
+        self.line += 1;
+        self.text = std::format!("{}| ", self.line);
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.text.as_bytes()
+    }
+}
+
+#[test]
+fn should_prefix_split_lines() {
+    let mut writer = PrefixedWriter::new(std::string::String::new(), LineNumber { line: 0, text: std::string::String::new() });
+    write!(writer, "first\nsec").expect("To write");
+    assert_eq!(writer.prefix().line, 2);
+    writeln!(writer, "ond").expect("To write");
+    //Prefix is taken lazily
+    assert_eq!(writer.prefix().line, 2);
+    write!(writer, "\nthird\nfourth").expect("To write");
+    assert_eq!(writer.get_ref(), "1| first\n2| second\n3| \n4| third\n5| fourth");
+
+    let mut writer = PrefixedWriter::new(std::string::String::new(), "> ");
+    for part in ["a", "b\n", "", "c\nd", "\n"] {
+        write!(writer, "{}", part).expect("To write");
+    }
+    assert_eq!(writer.get_ref(), "> ab\n> c\n> d\n");
+
+    //Prefix that is not UTF-8 cannot go through fmt::Write
+    let mut writer = PrefixedWriter::new(std::string::String::new(), &b"\xff "[..]);
+    assert!(write!(writer, "text").is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_prefix_lines_with_short_io_writes() {
+    use std::io::Write as _;
+
+    let mut writer = PrefixedWriter::new(ShortWriter { data: Vec::new(), limit: 3 }, "[INFO] ");
+    writer.write_all(b"first\nsecond line\n\nlast").expect("To write");
+    assert_eq!(writer.get_ref().data, b"[INFO] first\n[INFO] second line\n[INFO] \n[INFO] last");
+
+    //Single write never goes past line
+    let mut writer = PrefixedWriter::new(ShortWriter { data: Vec::new(), limit: 100 }, "> ");
+    assert_eq!(writer.write(b"ab\ncd").expect("To write"), 3);
+    assert_eq!(writer.write(b"").expect("To write"), 0);
+    assert_eq!(writer.get_ref().data, b"> ab\n");
+
+    //Byte prefix is written as it is
+    let mut writer = PrefixedWriter::new(ShortWriter { data: Vec::new(), limit: 100 }, &b"\x1b[2m|\x1b[0m "[..]);
+    writer.write_all(b"a\nb\n").expect("To write");
+    assert_eq!(writer.get_ref().data, b"\x1b[2m|\x1b[0m a\n\x1b[2m|\x1b[0m b\n");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn should_report_closed_socket_with_ignored_sigpipe() {
+    let (read, write) = packet_pair();
+    unsafe {
+        libc::close(read);
+    }
+
+    let mut writer = FdWriter::<16>::new_sized(write);
+    writer.set_ignore_sigpipe(true);
+    assert!(writeln!(writer, "line").is_err());
+    assert_eq!(writer.take_last_error().expect("To have error").errno(), libc::EPIPE);
+    assert!(writer.sink().ignore_sigpipe());
+    writer.clear();
+    drop(writer);
+    unsafe {
+        libc::close(write);
+    }
+
+    //Option turns itself off for non-socket
+    let (read, write) = pipe();
+    set_nonblocking(read);
+    let mut writer = FdWriter::<16>::new_sized(write);
+    writer.set_ignore_sigpipe(true);
+    assert!(writeln!(writer, "line").is_ok());
+    assert!(!writer.sink().ignore_sigpipe());
+    assert_eq!(read_all(read), b"line\n");
+
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_not_flush_after_into_parts() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = FdWriter::<8>::new_sized(write);
+    writer.write_data(b"line\n");
+    writer.write_data(b"rest");
+    let (sink, len, buffer) = writer.into_parts();
+    assert_eq!(sink.fd(), write);
+    assert_eq!(len, 4);
+    assert_eq!(buffer, *b"rest\0\0\0\0");
+    assert_eq!(read_all(read), b"line\n");
+
+    let mut writer = FdWriter::<8>::new_sized(write);
+    writer.write_data(b"last");
+    assert_eq!(writer.into_fd(), write);
+    assert_eq!(read_all(read), b"last");
+
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[test]
+fn should_report_would_block_from_io_write() {
+    use std::io::Write as _;
+
+    let (read, write) = pipe();
+    unsafe {
+        assert!(libc::fcntl(write, libc::F_SETPIPE_SZ, 4096) >= 4096);
+    }
+    set_nonblocking(write);
+    set_nonblocking(read);
+
+    let filler = [b'-'; 4096];
+    assert_eq!(unsafe { libc::write(write, filler.as_ptr() as _, filler.len()) }, filler.len() as isize);
+
+    let mut writer = FdWriter::<16>::new_sized(write);
+    assert_eq!(writer.write(b"pending").expect("To buffer"), 7);
+    let error = std::io::Write::flush(&mut writer).expect_err("To fail on full pipe");
+    assert_eq!(error.kind(), std::io::ErrorKind::WouldBlock);
+    assert_eq!(writer.as_slice(), b"pending");
+
+    assert_eq!(read_all(read), filler);
+    std::io::Write::flush(&mut writer).expect("To flush once pipe is drained");
+    assert_eq!(read_all(read), b"pending");
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_replace_fd_between_pipes() {
+    let (first_read, first_write) = pipe();
+    let (second_read, second_write) = pipe();
+    set_nonblocking(first_read);
+    set_nonblocking(second_read);
+
+    let mut writer = FdWriter::<16>::new_sized(first_write);
+    writer.write_data(b"first");
+    assert_eq!(writer.replace_fd(second_write), Ok(first_write));
+    assert_eq!(writer.fd(), second_write);
+    writer.write_data(b"second");
+    assert_eq!(writer.replace_fd(first_write), Ok(second_write));
+    writer.write_data(b"third");
+    writer.flush();
+
+    assert_eq!(read_all(first_read), b"firstthird");
+    assert_eq!(read_all(second_read), b"second");
+
+    //Failed flush keeps both file descriptor and data
+    unsafe {
+        libc::close(first_read);
+    }
+    writer.write_data(b"pending");
+    let error = writer.replace_fd(second_write).expect_err("To fail on closed pipe");
+    assert_eq!(error.errno(), libc::EPIPE);
+    assert_eq!(writer.fd(), first_write);
+    assert_eq!(writer.as_slice(), b"pending");
+    writer.clear();
+
+    unsafe {
+        libc::close(first_write);
+        libc::close(second_read);
+        libc::close(second_write);
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn should_write_non_utf8_path_through_pipe() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let path = std::path::Path::new(std::ffi::OsStr::from_bytes(b"/tmp/\xff\xfe-file"));
+    let name = std::ffi::CStr::from_bytes_with_nul(b"c\xc3\x28\0").expect("Valid C string");
+
+    let mut writer = FdWriter::<64>::new_sized(write);
+    writer.write_path(path);
+    writer.write_data(b" ");
+    writer.write_os_str(path.file_name().expect("File name"));
+    writer.write_data(b" ");
+    writer.write_cstr(name);
+    writer.flush();
+
+    assert_eq!(read_all(read), b"/tmp/\xff\xfe-file \xff\xfe-file c\xc3\x28");
+
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[test]
+fn should_write_atomic_record_or_nothing() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = FdWriter::<16>::new_sized(write);
+    writer.write_data(b"buffered ");
+    assert_eq!(writer.write_atomic(b"record\n"), Ok(true));
+    assert_eq!(read_all(read), b"buffered record\n");
+
+    let large = [b'x'; crate::PIPE_BUF + 1];
+    assert_eq!(writer.write_atomic(&large), Ok(false));
+    assert_eq!(read_all(read), b"");
+
+    //Partial write is not completed, as it would not be atomic
+    let mut writer = FdWriter::<16, _>::with_sink_sized(ShortWriter { data: Vec::new(), limit: 3 });
+    let error = writer.write_atomic(b"record").expect_err("To stop short");
+    assert_eq!(error.errno(), 0);
+    assert_eq!(error.written(), 3);
+    assert_eq!(writer.last_error(), Some(error));
+    assert_eq!(writer.sink().data, b"rec");
+    assert!(writer.is_empty());
+
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_not_close_fd_taken_from_owned_writer() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = unsafe {
+        OwnedFdWriter::<16>::new_sized(write)
+    };
+    writer.write_data(b"buffered");
+    assert_eq!(writer.into_raw_fd(), write);
+    assert_eq!(read_all(read), b"buffered");
+
+    //File descriptor is still open
+    assert_eq!(unsafe { libc::write(write, b"raw".as_ptr() as _, 3) }, 3);
+    assert_eq!(read_all(read), b"raw");
+
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn should_count_exact_stats() {
+    let mut writer = FdWriter::<8, _>::with_sink_sized(ShortWriter { data: Vec::new(), limit: 3 });
+    writer.write_data(b"abcd");
+    writer.write_data(b"ef\n");
+    //Flush of 7 bytes takes 3 short writes
+    assert_eq!(writer.sink().data, b"abcdef\n");
+    let stats = writer.stats();
+    assert_eq!(stats.bytes_written, 7);
+    assert_eq!(stats.writes, 3);
+    assert_eq!(stats.short_writes, 2);
+    assert_eq!(stats.errors, 0);
+    assert_eq!(stats.high_water_mark, 7);
+    assert_eq!(writer.bytes_written(), 7);
+
+    writer.reset_stats();
+    writer.write_data(b"ab");
+    assert_eq!(writer.stats(), crate::Stats {
+        bytes_written: 0,
+        writes: 0,
+        short_writes: 0,
+        errors: 0,
+        high_water_mark: 2,
+    });
+    writer.clear();
+
+    let (read, write) = pipe();
+    unsafe {
+        libc::close(read);
+    }
+    let mut writer = FdWriter::<8>::new_sized(write);
+    writer.write_data(b"line\n");
+    writer.write_data(b"x\n");
+    let stats = writer.stats();
+    assert_eq!(stats.bytes_written, 0);
+    assert_eq!(stats.writes, 2);
+    assert_eq!(stats.errors, 2);
+    assert_eq!(stats.high_water_mark, 7);
+    writer.clear();
+
+    unsafe {
+        libc::close(write);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_count_acknowledged_bytes() {
+    let mut writer = FdWriter::<8, _>::with_sink_sized(ShortWriter { data: Vec::new(), limit: 3 });
+    writer.set_auto_flush(false);
+    writer.write_data(b"abcde");
+    assert_eq!(writer.bytes_written(), 0);
+    assert_eq!(writer.bytes_buffered(), 5);
+
+    writer.flush();
+    assert_eq!(writer.bytes_written(), 5);
+    assert_eq!(writer.bytes_buffered(), 0);
+
+    writer.reset_bytes_written();
+    writer.write_data(b"f");
+    assert_eq!(writer.bytes_written(), 0);
+    assert_eq!(writer.bytes_buffered(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_flush_on_threshold() {
+    let mut writer = FdWriter::<16, Vec<u8>>::with_sink_sized(Vec::new());
+    assert_eq!(writer.flush_threshold(), 16);
+    writer.set_flush_threshold(100);
+    assert_eq!(writer.flush_threshold(), 16);
+    writer.set_flush_threshold(8);
+    assert_eq!(writer.flush_threshold(), 8);
+
+    for byte in b"1234567" {
+        writer.write_byte(*byte);
+    }
+    assert!(writer.sink().is_empty());
+    writer.write_byte(b'8');
+    assert_eq!(writer.sink().as_slice(), b"12345678");
+    assert!(writer.is_empty());
+
+    writer.write_data(b"abcde");
+    assert_eq!(writer.len(), 5);
+    writer.write_data(b"fghij");
+    assert_eq!(writer.sink().as_slice(), b"12345678abcdefghij");
+    assert!(writer.is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_write_through_boxed_buffer() {
+    let mut writer = crate::FdWriterBoxed::with_sink_capacity(Vec::new(), 5);
+    assert_eq!(writer.capacity(), 5);
+    writer.set_auto_flush(false);
+
+    writer.write_data(b"abc");
+    assert_eq!(writer.remaining(), 2);
+    assert_eq!(writer.as_slice(), b"abc");
+    writer.write_data(b"defghij");
+    assert_eq!(writer.sink().as_slice(), b"abcdefghij");
+    assert!(writer.is_empty());
+
+    writer.write_data(b"kl");
+    assert_eq!(writer.into_sink(), b"abcdefghijkl");
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_header_at_offset_without_flush() {
+    let (path, fd) = temp_file("write_at");
+
+    let mut writer = FdWriter::new(fd);
+    writer.write_data(b"header: 0\nbody\n");
+    writer.set_auto_flush(false);
+    writer.write_data(b"pending");
+    //Buffer is neither flushed nor modified
+    assert_eq!(writer.write_at(b"1", 8), Ok(1));
+    assert_eq!(writer.as_slice(), b"pending");
+    assert_eq!(unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) }, 15);
+
+    writer.flush();
+    drop(writer);
+    unsafe {
+        libc::close(fd);
+    }
+
+    let content = std::fs::read(&path).expect("To read file");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(content, b"header: 1\nbody\npending");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_flush_according_to_auto_flush_policy() {
+    use crate::AutoFlush;
+
+    let writer = FdWriter::with_auto_flush(1, AutoFlush::OnByte(0));
+    assert_eq!(writer.auto_flush(), AutoFlush::OnByte(0));
+    assert_eq!(writer.mode(), crate::BufferMode::Line);
+    assert_eq!(FdWriter::new(1).auto_flush(), AutoFlush::OnByte(b'\n'));
+
+    let mut writer = FdWriter::<16, Vec<u8>>::with_sink_sized(Vec::new());
+    writer.set_auto_flush_policy(AutoFlush::OnByte(0));
+    writer.write_data(b"first\0sec");
+    assert_eq!(writer.sink().as_slice(), b"first\0");
+    writer.write_data(b"ond\n");
+    assert_eq!(writer.as_slice(), b"second\n");
+
+    writer.set_auto_flush_policy(AutoFlush::Never);
+    assert_eq!(writer.mode(), crate::BufferMode::Full);
+    writer.write_data(b"\0");
+    assert_eq!(writer.sink().as_slice(), b"first\0");
+
+    writer.set_auto_flush_policy(AutoFlush::Always);
+    assert_eq!(writer.auto_flush(), AutoFlush::Always);
+    writer.write_data(b"x");
+    assert_eq!(writer.sink().as_slice(), b"first\0second\n\0x");
+    assert!(writer.is_empty());
+
+    //Boolean shortcut keeps delimiter
+    writer.set_auto_flush_policy(AutoFlush::OnByte(b';'));
+    writer.set_auto_flush(false);
+    assert_eq!(writer.auto_flush(), AutoFlush::Never);
+    writer.set_auto_flush(true);
+    assert_eq!(writer.auto_flush(), AutoFlush::OnByte(b';'));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_write_through_caller_buffer() {
+    let mut buffer = [0u8; 5];
+    let mut writer = crate::FdWriterRef::with_sink_buffer(Vec::new(), &mut buffer);
+    assert_eq!(writer.capacity(), 5);
+
+    writer.write_data(b"ab");
+    assert_eq!(writer.remaining(), 3);
+    writer.write_data(b"c\nde");
+    assert_eq!(writer.sink().as_slice(), b"abc\n");
+    assert_eq!(writer.as_slice(), b"de");
+    writer.write_data(b"fghijk");
+    assert_eq!(writer.into_sink(), b"abc\ndefghijk");
+    //Buffer is available once writer is gone
+    assert_eq!(buffer[..2], *b"de");
+}
+
+#[test]
+fn should_handle_short_writes() {
+    let sink = ScriptedSink::new(&[]).then(Step::Accept(3));
+    let mut writer = FdWriter::<8, _>::with_sink_sized(sink);
+    writer.set_mode(BufferMode::Full);
+
+    let data = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    writer.write_data(data);
+    writer.try_flush().expect("To flush");
+
+    assert!(writer.is_empty());
+    assert_eq!(writer.sink().data, data);
+    assert!(writer.sink().writes.iter().all(|write| write.len() <= 3));
+    assert!(writer.stats().short_writes > 0);
+}
+
+#[test]
+fn should_keep_unwritten_data_after_failed_short_write() {
+    let sink = ScriptedSink::new(&[Step::Accept(3), Step::Fail(libc::EIO)]);
+    let mut writer = FdWriter::<8, _>::with_sink_sized(sink);
+    writer.set_mode(BufferMode::Full);
+
+    writer.write_data(b"abcdefg");
+    let error = writer.try_flush().expect_err("To fail flush");
+    assert_eq!(error.errno(), libc::EIO);
+    assert_eq!(error.written(), 3);
+    assert_eq!(writer.as_slice(), b"defg");
+    assert_eq!(writer.last_error(), Some(error));
+
+    writer.try_flush().expect("To flush rest");
+    assert_eq!(writer.sink().data, b"abcdefg");
+}
+
+#[cfg(not(windows))]
+#[test]
+fn should_retry_eintr_of_sink() {
+    let sink = ScriptedSink::new(&[Step::Fail(libc::EINTR), Step::Accept(2), Step::Fail(libc::EINTR)]);
+    let mut writer = FdWriter::<8, _>::with_sink_sized(sink);
+
+    writer.write_data(b"line\n");
+    assert!(writer.is_empty());
+    assert_eq!(writer.sink().data, b"line\n");
+    assert_eq!(writer.last_error(), None);
+
+    //Buffer and data written together
+    let sink = ScriptedSink::new(&[Step::Fail(libc::EINTR), Step::Accept(2), Step::Fail(libc::EINTR)]);
+    let mut writer = FdWriter::<4, _>::with_sink_sized(sink);
+    writer.set_mode(BufferMode::Full);
+    writer.write_data(b"abc");
+    writer.write_data(b"defghijk");
+    writer.try_flush().expect("To flush");
+    assert_eq!(writer.sink().data, b"abcdefghijk");
+    assert_eq!(writer.last_error(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_keep_buffer_on_would_block() {
+    use std::io::Write;
+
+    let sink = ScriptedSink::new(&[]).then(Step::Fail(libc::EAGAIN));
+    let mut writer = FdWriter::<8, _>::with_sink_sized(sink);
+    writer.set_mode(BufferMode::Full);
+
+    assert_eq!(writer.write(b"abcdefgh").expect("To buffer"), 8);
+    let error = writer.write(b"ij").expect_err("To block");
+    assert_eq!(error.kind(), std::io::ErrorKind::WouldBlock);
+    assert_eq!(writer.as_slice(), b"abcdefgh");
+    assert_eq!(std::io::Write::flush(&mut writer).expect_err("To block").kind(), std::io::ErrorKind::WouldBlock);
+    assert_eq!(writer.len(), 8);
+
+    writer.sink_mut().then = None;
+    writer.write_all(b"ij").expect("To write");
+    writer.try_flush().expect("To flush");
+    assert_eq!(writer.sink().data, b"abcdefghij");
+}
+
+#[test]
+fn should_write_through_7_byte_buffer() {
+    let mut payload = Vec::new();
+    for idx in 0..40 {
+        writeln!(Adapter(&mut payload), "line {}: {}", idx, "y".repeat(idx % 11)).expect("To format");
+    }
+    assert!(payload.len() > 300);
+
+    for (mode, script) in [(BufferMode::Line, None), (BufferMode::Full, None), (BufferMode::Line, Some(Step::Accept(5))), (BufferMode::Full, Some(Step::Accept(2)))] {
+        for chunk in [1, 3, 7, 8, 13, 100] {
+            let mut buffer = [0u8; 7];
+            let sink = match script {
+                Some(step) => ScriptedSink::new(&[]).then(step),
+                None => ScriptedSink::default(),
+            };
+            let mut writer = FdWriterRef::with_sink_buffer(sink, &mut buffer);
+            assert_eq!(writer.capacity(), 7);
+            writer.set_mode(mode);
+
+            for part in payload.chunks(chunk) {
+                writer.write_data(part);
+            }
+            writer.try_flush().expect("To flush");
+            assert_eq!(writer.sink().data, payload, "mode={:?} chunk={}", mode, chunk);
+        }
+    }
+}
+
+#[test]
+fn should_prefix_timestamp() {
+    let mut writer = crate::TimestampWriter::new(std::string::String::new(), crate::Timestamp::new(crate::Clock::Realtime));
+    write!(writer, "one\ntw").expect("To write");
+    write!(writer, "o\nthree\n").expect("To write");
+
+    let lines = writer.get_ref().lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    for (line, text) in lines.iter().zip(["one", "two", "three"]) {
+        let (stamp, rest) = line.split_once(' ').expect("Timestamp");
+        assert_eq!(rest, text);
+        let (secs, millis) = stamp.split_once('.').expect("Fraction");
+        assert!(secs.parse::<u64>().expect("Seconds") > 0);
+        assert_eq!(millis.len(), 3);
+        assert!(millis.bytes().all(|byte| byte.is_ascii_digit()));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_every_call_out_when_unbuffered() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = FdWriter::new_unbuffered(write);
+    for part in [&b"a"[..], b"bc", b"def\n", b"g"] {
+        writer.write_data(part);
+        assert!(writer.is_empty());
+        assert_eq!(read_all(read), part);
+    }
+    assert_eq!(writer.stats().writes, 4);
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[test]
+fn should_prefix_lines_with_prefix_longer_than_buffer() {
+    let writer = FdWriter::<8, _>::with_sink_sized(ScriptedSink::default());
+    let mut writer = PrefixedWriter::new(writer, "[worker3] ");
+    assert!("[worker3] ".len() > writer.get_ref().capacity());
+
+    for part in ["one\n", "", "\n", "tw", "o\nthree is longer than buffer\n"] {
+        write!(writer, "{}", part).expect("To write");
+        //Newline flushing is not affected by prefix
+        assert_eq!(writer.get_ref().is_empty(), part != "tw");
+    }
+    write!(writer, "four").expect("To write");
+    assert_eq!(writer.get_ref().as_slice(), b"four");
+
+    let mut writer = writer.into_inner();
+    writer.flush();
+    assert_eq!(writer.sink().data, b"[worker3] one\n[worker3] \n[worker3] two\n[worker3] three is longer than buffer\n[worker3] four");
+}
+
+#[cfg(all(feature = "log", unix))]
+#[test]
+fn should_log_records_up_to_level() {
+    use log::Log;
+
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let logger = crate::FdWriterLogger::new(write, log::LevelFilter::Info);
+    logger.log(&log::Record::builder().level(log::Level::Info).target("net").args(format_args!("up {}", 1)).build());
+    logger.log(&log::Record::builder().level(log::Level::Debug).target("net").args(format_args!("hidden")).build());
+    assert_eq!(read_all(read), b"[INFO net] up 1\n");
+
+    logger.set_level(log::LevelFilter::Debug);
+    assert_eq!(logger.level(), log::LevelFilter::Debug);
+    logger.log(&log::Record::builder().level(log::Level::Debug).target("net").args(format_args!("shown")).build());
+    assert_eq!(read_all(read), b"[DEBUG net] shown\n");
+
+    drop(logger);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn should_seek_and_overwrite_file() {
+    use std::io::{Seek, SeekFrom};
+
+    let (path, fd) = temp_file("seek");
+
+    let mut writer = FdWriter::with_mode(fd, crate::BufferMode::Full);
+    writer.write_data(b"hello world");
+    //Buffered data is written before moving position
+    assert_eq!(writer.seek(SeekFrom::Start(0)).expect("To seek"), 0);
+    assert!(writer.is_empty());
+    writer.write_data(b"J");
+    assert_eq!(writer.seek(SeekFrom::End(0)).expect("To seek"), 11);
+    writer.write_data(b"!");
+    writer.flush();
+    drop(writer);
+
+    //Wrappers seek their file descriptor too
+    let file = std::fs::OpenOptions::new().write(true).open(&path).expect("To open file");
+    let mut writer = FdWriter::from_owned(file.into());
+    assert_eq!(writer.seek(SeekFrom::Start(6)).expect("To seek"), 6);
+    writer.write_data(b"W");
+    assert_eq!(writer.stream_position().expect("To get position"), 7);
+    drop(writer);
+
+    unsafe {
+        libc::close(fd);
+    }
+    let content = std::fs::read(&path).expect("To read file");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(content, b"Jello World!");
+
+    //Pipes cannot seek
+    let (read, write) = pipe();
+    let mut writer = FdWriter::new(write);
+    assert_eq!(writer.seek(SeekFrom::Start(0)).expect_err("To fail").raw_os_error(), Some(libc::ESPIPE));
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[test]
+fn should_strip_split_escape_sequences() {
+    let input = "plain \x1b[1;31mred\x1b[0m \x1b]0;title\x07osc \x1b]8;;http://x\x1b\\link\x1b]8;;\x1b\\ \x1b7save\x1b8 \x1bc\x1b(Bcharset\x1b[?25h end";
+    let expected = "plain red osc link save charset end";
+
+    for split in 0..=input.len() {
+        let mut writer = crate::StripAnsiWriter::new(std::string::String::new());
+        write!(writer, "{}", &input[..split]).expect("To write");
+        write!(writer, "{}", &input[split..]).expect("To write");
+        assert_eq!(writer.get_ref(), expected, "split at {}", split);
+    }
+
+    let mut writer = crate::StripAnsiWriter::new(std::string::String::new());
+    for idx in 0..input.len() {
+        write!(writer, "{}", &input[idx..idx + 1]).expect("To write");
+    }
+    assert_eq!(writer.get_ref(), expected);
+}
+
+#[test]
+fn should_strip_malformed_escape_sequences() {
+    let mut writer = crate::StripAnsiWriter::new(std::string::String::new());
+    write!(writer, "a\x1b\nb\x1b[12\x01c\x1b\x1b[0md").expect("To write");
+    assert_eq!(writer.get_ref(), "a\nb\x01cd");
+
+    let mut writer = crate::StripAnsiWriter::new(std::string::String::new());
+    writeln!(writer, "plain text, нет escape").expect("To write");
+    assert_eq!(writer.get_ref(), "plain text, нет escape\n");
+
+    let mut writer = crate::StripAnsiWriter::new(std::string::String::new());
+    writer.set_enabled(false);
+    write!(writer, "\x1b[31mred").expect("To write");
+    assert_eq!(writer.get_ref(), "\x1b[31mred");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_strip_escape_sequences_with_short_io_writes() {
+    use std::io::Write as _;
+
+    let mut writer = crate::StripAnsiWriter::new(ShortWriter { data: Vec::new(), limit: 2 });
+    writer.write_all(b"\x1b[1mbold\x1b[0m \x1b]2;t\x07text").expect("To write");
+    assert_eq!(writer.get_ref().data, b"bold text");
+
+    let mut writer = crate::StripAnsiWriter::new(ShortWriter { data: Vec::new(), limit: 100 });
+    //Escape sequence is consumed together with text after it
+    assert_eq!(writer.write(b"\x1b[1mab\x1b[0m").expect("To write"), 6);
+    assert_eq!(writer.write(b"\x1b[0m").expect("To write"), 4);
+    assert_eq!(writer.get_ref().data, b"ab");
+}
+
+#[cfg(unix)]
+#[test]
+fn should_strip_escape_sequences_when_not_terminal() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let writer = FdWriter::new(write);
+    assert!(!writer.is_terminal());
+    let mut writer = crate::StripAnsiWriter::new_auto(writer);
+    assert!(writer.is_enabled());
+    writeln!(writer, "\x1b[32mok\x1b[0m").expect("To write");
+    assert_eq!(read_all(read), b"ok\n");
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[test]
+fn should_translate_crlf() {
+    let mut writer = crate::CrlfWriter::new(std::string::String::new());
+    for part in ["a\nb\r", "\n", "\n\nc"] {
+        write!(writer, "{}", part).expect("To write");
+    }
+    assert_eq!(writer.get_ref(), "a\r\nb\r\n\r\n\r\nc");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_translate_crlf_with_short_io_writes() {
+    use std::io::Write as _;
+
+    let mut writer = crate::CrlfWriter::new(ShortWriter { data: Vec::new(), limit: 1 });
+    writer.write_all(b"ab\n\ncd\r\ne\r").expect("To write");
+    writer.write_all(b"\n").expect("To write");
+    assert_eq!(writer.get_ref().data, b"ab\r\n\r\ncd\r\ne\r\n");
+}
+
+#[test]
+fn should_never_split_crlf_across_flushes() {
+    let mut payload = Vec::new();
+    for idx in 0..30 {
+        writeln!(Adapter(&mut payload), "{}{}", idx, "z".repeat(idx % 9)).expect("To format");
+    }
+    payload.extend_from_slice(b"\n\n\nend");
+    let mut expected = Vec::new();
+    for byte in payload.iter() {
+        if *byte == b'\n' {
+            expected.push(b'\r');
+        }
+        expected.push(*byte);
+    }
+
+    for mode in [BufferMode::Line, BufferMode::Full, BufferMode::None] {
+        for chunk in [1, 2, 3, 7, 8, 9, 100] {
+            let mut writer = FdWriter::<8, _>::with_sink_sized(ScriptedSink::default());
+            writer.set_crlf(true);
+            writer.set_mode(mode);
+
+            for part in payload.chunks(chunk) {
+                match part {
+                    [byte] => writer.write_byte(*byte),
+                    part => assert_eq!(writer.try_write_data(part), Ok(part.len())),
+                }
+                if mode != BufferMode::Full && part.last() == Some(&b'\n') {
+                    assert!(writer.is_empty(), "mode={:?} chunk={}", mode, chunk);
+                }
+            }
+            writer.try_flush().expect("To flush");
+
+            let sink = writer.sink();
+            assert_eq!(sink.data, expected, "mode={:?} chunk={}", mode, chunk);
+            for write in sink.writes.iter() {
+                assert_ne!(write.last(), Some(&b'\r'), "mode={:?} chunk={}", mode, chunk);
+                assert_ne!(write.first(), Some(&b'\n'), "mode={:?} chunk={}", mode, chunk);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_write_crlf_into_pipe() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = FdWriter::with_crlf(write);
+    assert!(writer.crlf());
+    writeln!(writer, "HTTP/1.1 200 OK").expect("To write");
+    assert_eq!(read_all(read), b"HTTP/1.1 200 OK\r\n");
+
+    writer.set_crlf(false);
+    writeln!(writer, "plain").expect("To write");
+    assert_eq!(read_all(read), b"plain\n");
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_flush_at_watermark_into_pipe() {
+    let (read, write) = pipe();
+    set_nonblocking(read);
+
+    let mut writer = FdWriter::with_mode(write, crate::BufferMode::Full);
+    writer.set_flush_threshold(16);
+    writer.write_data(b"0123456789");
+    //Below watermark nothing arrives
+    assert_eq!(read_all(read), b"");
+    writer.write_data(b"abcde");
+    assert_eq!(read_all(read), b"");
+    writer.write_data(b"f");
+    assert_eq!(read_all(read), b"0123456789abcdef");
+    assert!(writer.is_empty());
+
+    writer.set_flush_threshold(0);
+    writer.write_data(b"g");
+    assert_eq!(read_all(read), b"g");
+
+    writer.set_flush_threshold(usize::MAX);
+    assert_eq!(writer.flush_threshold(), writer.capacity());
+
+    drop(writer);
+    unsafe {
+        libc::close(read);
+        libc::close(write);
+    }
+}
+
+#[test]
+fn should_flush_at_end_of_guard_scope() {
+    let mut writer = FdWriter::<16, _>::with_sink_sized(ScriptedSink::default());
+    writer.set_mode(BufferMode::Full);
+    {
+        let mut guard = writer.flush_guard();
+        guard.write_data(b"scoped");
+        assert_eq!(guard.len(), 6);
+    }
+    assert!(writer.is_empty());
+    assert_eq!(writer.sink().data, b"scoped");
+
+    writer.sink_mut().then = Some(Step::Fail(libc::EIO));
+    let mut guard = writer.flush_guard();
+    guard.write_data(b"kept");
+    assert_eq!(guard.finish().map_err(|error| error.errno()), Err(libc::EIO));
+    assert_eq!(writer.as_slice(), b"kept");
+}
@@ -0,0 +1,108 @@
+use core::{fmt, time};
+
+use crate::{sys, LinePrefix, PrefixedWriter};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+///Clock used by `Timestamp`.
+pub enum Clock {
+    ///Monotonic clock, counting from unspecified point (typically boot).
+    ///
+    ///This is default clock.
+    Monotonic,
+    ///Wall clock, counting from UNIX epoch.
+    Realtime,
+}
+
+impl Clock {
+    #[inline]
+    ///Returns current time of clock.
+    pub fn now(self) -> time::Duration {
+        match self {
+            Clock::Monotonic => sys::monotonic(),
+            Clock::Realtime => sys::realtime(),
+        }
+    }
+}
+
+//Maximum length of `seconds.millis ` for `u64` seconds.
+const STAMP_LEN: usize = 25;
+
+//Formats time as `seconds.millis `, returning buffer and start of text within it.
+fn format_stamp(time: time::Duration) -> ([u8; STAMP_LEN], usize) {
+    let mut buffer = [0; STAMP_LEN];
+    let mut idx = STAMP_LEN - 5;
+    buffer[idx..].copy_from_slice(b".000 ");
+
+    let mut millis = time.subsec_millis();
+    for byte in buffer[idx + 1..idx + 4].iter_mut().rev() {
+        *byte = b'0' + (millis % 10) as u8;
+        millis /= 10;
+    }
+
+    let mut secs = time.as_secs();
+    loop {
+        idx -= 1;
+        buffer[idx] = b'0' + (secs % 10) as u8;
+        secs /= 10;
+        if secs == 0 {
+            break (buffer, idx);
+        }
+    }
+}
+
+///Prefix of `PrefixedWriter`, which writes timestamp formatted as `seconds.millis `.
+///
+///Timestamp is taken lazily, when first byte of new line is written.
+pub struct Timestamp {
+    clock: Clock,
+    stamp: [u8; STAMP_LEN],
+    start: usize,
+}
+
+impl Timestamp {
+    #[inline(always)]
+    ///Creates new instance, which takes time of `clock`
+    pub const fn new(clock: Clock) -> Self {
+        Self {
+            clock,
+            stamp: [0; STAMP_LEN],
+            start: STAMP_LEN,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns clock used for timestamps.
+    pub const fn clock(&self) -> Clock {
+        self.clock
+    }
+}
+
+impl Default for Timestamp {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(Clock::Monotonic)
+    }
+}
+
+impl LinePrefix for Timestamp {
+    #[inline]
+    fn start_line(&mut self) {
+        (self.stamp, self.start) = format_stamp(self.clock.now());
+    }
+
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        &self.stamp[self.start..]
+    }
+}
+
+impl fmt::Debug for Timestamp {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Timestamp").field("clock", &self.clock).finish()
+    }
+}
+
+///Writer that writes timestamp at the start of every line, formatted as `seconds.millis `.
+///
+///Usage: `TimestampWriter::new(inner, Timestamp::new(Clock::Realtime))`
+pub type TimestampWriter<W> = PrefixedWriter<W, Timestamp>;
@@ -0,0 +1,191 @@
+use core::pin::Pin;
+use core::task::{Context, Poll};
+#[cfg(all(feature = "tokio", unix))]
+use core::task::ready;
+
+use ::tokio::io::AsyncWrite;
+#[cfg(all(feature = "tokio", unix))]
+use ::tokio::io::{unix::AsyncFd, Interest};
+
+use crate::{FdWriter, RawSink};
+#[cfg(all(feature = "tokio", unix))]
+use crate::{sys, RawFd, WriteError, BUFFER_CAPACITY};
+
+///Buffers data same as `try_write_data` and writes it out on `poll_flush`.
+///
+///`FdWriter` is not registered with reactor, hence file descriptor must be put in non-blocking mode by caller,
+///otherwise `write` blocks executor thread.
+///
+///When `write` fails with `EAGAIN`, error of kind `WouldBlock` is returned instead of `Poll::Pending`,
+///as there is no readiness notification to wake task once file descriptor becomes writable.
+///To wait for it, register file descriptor with reactor (e.g. `tokio::io::unix::AsyncFd`) and retry once it is ready.
+impl<const N: usize, S: RawSink + Unpin> AsyncWrite for FdWriter<N, S> {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(self.get_mut().try_write_data(buf).map_err(Into::into))
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(self.get_mut().try_flush().map_err(Into::into))
+    }
+
+    #[inline(always)]
+    ///Flushes buffer, file descriptor is not closed.
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+///Writer which waits for file descriptor to become writable using reactor of tokio runtime.
+///
+///File descriptor is put in non-blocking mode and registered with reactor on creation,
+///hence it must be created within runtime with IO enabled.
+///Unless file descriptor is owned, its original flags are restored on shutdown or drop.
+///
+///Buffering is the same as `FdWriter`, whose `write` is performed only when file descriptor is ready.
+///
+///Use `shutdown` to make sure that buffered data is written out.
+///Drop cannot wait for file descriptor to become writable, so it writes buffered data of owned file descriptor
+///only once, discarding whatever is not accepted due to `EAGAIN`.
+///Borrowed file descriptor is switched back to its original (usually blocking) mode before final flush,
+///so drop may block in this case.
+pub struct AsyncFdWriter<const N: usize = BUFFER_CAPACITY> {
+    //Only `None` once file descriptor is deregistered on shutdown.
+    inner: Option<AsyncFd<FdWriter<N>>>,
+    owned: bool,
+    //Flags of file descriptor before it was put in non-blocking mode.
+    flags: libc::c_int,
+}
+
+#[cfg(all(feature = "tokio", unix))]
+impl<const N: usize> AsyncFdWriter<N> {
+    #[inline]
+    ///Creates new instance which writes into `fd`, which is never closed by writer.
+    pub fn new(fd: RawFd) -> std::io::Result<Self> {
+        Self::register(FdWriter::new_sized(fd), false)
+    }
+
+    #[inline]
+    ///Creates new instance which writes into `fd`, closing it on shutdown or drop.
+    pub fn from_owned(fd: std::os::fd::OwnedFd) -> std::io::Result<Self> {
+        Self::register(FdWriter::new_sized(std::os::fd::IntoRawFd::into_raw_fd(fd)), true)
+    }
+
+    fn register(writer: FdWriter<N>, owned: bool) -> std::io::Result<Self> {
+        let fd = writer.fd();
+        let flags = match sys::set_nonblocking(fd) {
+            Ok(flags) => flags,
+            Err(errno) => {
+                if owned {
+                    let _ = sys::close(fd);
+                }
+                return Err(std::io::Error::from_raw_os_error(errno));
+            },
+        };
+
+        match AsyncFd::with_interest(writer, Interest::WRITABLE) {
+            Ok(inner) => Ok(Self {
+                inner: Some(inner),
+                owned,
+                flags,
+            }),
+            Err(error) => {
+                let _ = match owned {
+                    true => sys::close(fd),
+                    false => sys::set_flags(fd, flags),
+                };
+                Err(error)
+            },
+        }
+    }
+
+    #[inline]
+    ///Returns reference to underlying writer, unless it is shut down.
+    pub fn get_ref(&self) -> Option<&FdWriter<N>> {
+        self.inner.as_ref().map(AsyncFd::get_ref)
+    }
+
+    #[inline]
+    ///Returns mutable reference to underlying writer, unless it is shut down.
+    ///
+    ///Writing via `FdWriter` directly returns `EAGAIN` instead of waiting for file descriptor to become writable.
+    pub fn get_mut(&mut self) -> Option<&mut FdWriter<N>> {
+        self.inner.as_mut().map(AsyncFd::get_mut)
+    }
+
+    //Performs `op` once file descriptor is writable, retrying it until it no longer fails with `EAGAIN`.
+    fn poll_io<T>(&mut self, cx: &mut Context<'_>, mut op: impl FnMut(&mut FdWriter<N>) -> Result<T, WriteError>) -> Poll<std::io::Result<T>> {
+        let inner = match self.inner.as_mut() {
+            Some(inner) => inner,
+            None => return Poll::Ready(Err(std::io::Error::from_raw_os_error(libc::EBADF))),
+        };
+
+        loop {
+            let mut guard = ready!(inner.poll_write_ready_mut(cx))?;
+            if let Ok(result) = guard.try_io(|inner| op(inner.get_mut()).map_err(Into::into)) {
+                return Poll::Ready(result);
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+impl<const N: usize> AsyncWrite for AsyncFdWriter<N> {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        self.get_mut().poll_io(cx, |writer| writer.try_write_data(buf))
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.get_mut().poll_io(cx, FdWriter::try_flush)
+    }
+
+    ///Flushes buffer and deregisters file descriptor from reactor.
+    ///
+    ///Owned file descriptor is closed, while original flags of borrowed one are restored.
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if this.inner.is_none() {
+            return Poll::Ready(Ok(()));
+        }
+
+        ready!(this.poll_io(cx, FdWriter::try_flush))?;
+        if let Some(inner) = this.inner.take() {
+            //Deregister before closing, so that fd can be reused safely
+            let fd = inner.into_inner().into_fd();
+            let result = match this.owned {
+                true => sys::close(fd),
+                false => sys::set_flags(fd, this.flags),
+            };
+            result.map_err(std::io::Error::from_raw_os_error)?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+impl<const N: usize> core::fmt::Debug for AsyncFdWriter<N> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.debug_struct("AsyncFdWriter").field("inner", &self.get_ref()).field("owned", &self.owned).finish()
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+impl<const N: usize> Drop for AsyncFdWriter<N> {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.take() {
+            let mut writer = inner.into_inner();
+            if self.owned {
+                //Single attempt, as there is no way to wait for readiness.
+                let _ = sys::close(writer.into_fd());
+            } else {
+                let _ = sys::set_flags(writer.fd(), self.flags);
+                writer.flush();
+            }
+        }
+    }
+}
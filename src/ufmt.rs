@@ -0,0 +1,24 @@
+use crate::{FdWriter, RawSink, WriteError};
+
+impl<const N: usize, S: RawSink> ::ufmt::uWrite for FdWriter<N, S> {
+    type Error = WriteError;
+
+    ///Writes text unto buffer, same as `fmt::Write::write_str`, but failing with actual error.
+    ///
+    ///Error of automatic flush is returned too, even though text is already in buffer.
+    fn write_str(&mut self, text: &str) -> Result<(), Self::Error> {
+        let previous = self.last_error.take();
+
+        match self.try_write_data(text.as_bytes()) {
+            Ok(written) if written == text.len() => match self.last_error {
+                Some(error) => Err(error),
+                None => {
+                    self.last_error = previous;
+                    Ok(())
+                },
+            },
+            Ok(_) => Err(self.last_error.unwrap_or(WriteError::new(0))),
+            Err(error) => Err(error),
+        }
+    }
+}